@@ -1,47 +1,85 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
 
-use num::integer::sqrt;
-
-use super::{get_lines, Day};
+use super::{get_lines, Day, Params};
+use crate::utils::polygon::Polygon;
 
 type Coord = (i32, i32);
 type Map = HashMap<Coord, Point>;
 
 pub struct Day10 {
     input: String,
+    params: Params,
 }
 
 impl Day10 {
-    pub fn new(input: String) -> Day10 {
-        Day10 { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["enclosure"];
+
+    pub fn new(input: String, params: Params) -> Day10 {
+        Day10 { input, params }
     }
 
-    fn parse_input(&self) -> (Coord, Map) {
+    /// returns the start and the map, with `S` replaced by the pipe hidden under it
+    fn parse_input(&self) -> Result<(Coord, Map), StartError> {
         let mut map = Map::new();
-        let mut start = (0, 0);
+        let mut start = None;
         let lines = get_lines(&self.input);
         for (y, line) in lines.iter().enumerate() {
             for (x, c) in line.chars().enumerate() {
                 let coord = (x as i32, y as i32);
                 let point = c.into();
                 if point == Point::Start {
-                    start = coord;
+                    start = Some(coord);
                 }
                 map.insert(coord, point);
             }
         }
-        (start, map)
+        let start = start.ok_or(StartError::Missing)?;
+        let start_pipe = Self::infer_start_pipe(&start, &map)?;
+        map.insert(start, start_pipe);
+        Ok((start, map))
+    }
+
+    /// works out the pipe under `S` from the neighbours that connect back to it
+    fn infer_start_pipe(start: &Coord, map: &Map) -> Result<Point, StartError> {
+        let neighbours = [
+            (Direction::North, (start.0, start.1 - 1)),
+            (Direction::South, (start.0, start.1 + 1)),
+            (Direction::East, (start.0 + 1, start.1)),
+            (Direction::West, (start.0 - 1, start.1)),
+        ];
+        let connected = neighbours
+            .iter()
+            .filter(|(direction, coord)| {
+                map.get(coord)
+                    .is_some_and(|point| point.opens_to(direction.opposite()))
+            })
+            .map(|(direction, _)| *direction)
+            .collect::<Vec<Direction>>();
+        match connected[..] {
+            [Direction::North, Direction::South] => Ok(Point::NorthSouth),
+            [Direction::North, Direction::East] => Ok(Point::NorthEast),
+            [Direction::North, Direction::West] => Ok(Point::NorthWest),
+            [Direction::South, Direction::East] => Ok(Point::SouthEast),
+            [Direction::South, Direction::West] => Ok(Point::SouthWest),
+            [Direction::East, Direction::West] => Ok(Point::EastWest),
+            _ => Err(StartError::Connections(connected.len())),
+        }
     }
 
     ///returns a list of points that are interior to the path
     fn find_interior_points(map: &Map, curve: &HashMap<Coord, i32>) -> Vec<Coord> {
         let mut inner_points = Vec::new();
-        // only works for square maps
-        let side_len = sqrt(map.len() as i32 + 1);
+        let width = map.keys().map(|(x, _)| *x).max().unwrap_or(-1) + 1;
+        let height = map.keys().map(|(_, y)| *y).max().unwrap_or(-1) + 1;
         // the direction of loop is currently up
         let mut curr_up = None;
-        for y in 0..side_len {
-            for x in 0..side_len {
+        for y in 0..height {
+            for x in 0..width {
                 let coord = (x, y);
                 if !map.contains_key(&coord) {
                     continue;
@@ -54,19 +92,6 @@ impl Day10 {
                         Point::NorthEast | Point::NorthWest | Point::Ground | Point::EastWest => {
                             // do nothing
                         }
-                        Point::Start => {
-                            // check if you can go south from Start
-                            if let Some(point) = map.get(&(x, y + 1)) {
-                                if Point::Start.check_next_point_valid(point, Direction::South) {
-                                    // if first upwards, then curr_up is true, else just switch directions
-                                    if curr_up.is_none() {
-                                        curr_up = Some(true)
-                                    } else {
-                                        curr_up = Some(!curr_up.unwrap());
-                                    }
-                                }
-                            }
-                        }
                         _ => {
                             // if first upwards, then curr_up is true, else just switch directions
                             if curr_up.is_none() {
@@ -90,59 +115,132 @@ impl Day10 {
         inner_points
     }
 
-    /// returns the direction from the start point
-    fn find_start_direction(start: &Coord, map: &Map) -> (Coord, Point) {
-        let checks = vec![
-            (start.0, start.1 - 1),
-            (start.0, start.1 + 1),
-            (start.0 - 1, start.1),
-            (start.0 + 1, start.1),
-        ];
-        for check in checks {
-            if let Some(point) = map.get(&check) {
-                if Point::Start.check_next_point_valid(point, Direction::from((*start, check))) {
-                    return (check, *point);
+    /// loop tiles in the order they are walked, starting from `S`
+    fn ordered_loop(curve: &HashMap<Coord, i32>) -> Vec<Coord> {
+        let mut vertices = curve.iter().collect::<Vec<(&Coord, &i32)>>();
+        vertices.sort_by_key(|(_, steps)| **steps);
+        vertices.into_iter().map(|(coord, _)| *coord).collect()
+    }
+
+    /// counts the interior from the loop's area (shoelace) and length (Pick's theorem)
+    fn count_interior_shoelace(curve: &HashMap<Coord, i32>) -> usize {
+        let vertices = Self::ordered_loop(curve)
+            .into_iter()
+            .map(|(x, y)| (x as i64, y as i64))
+            .collect();
+        Polygon::new(vertices).interior_points() as usize
+    }
+
+    fn count_enclosed(map: &Map, curve: &HashMap<Coord, i32>, enclosure: Enclosure) -> usize {
+        match enclosure {
+            Enclosure::Scanline => Self::find_interior_points(map, curve).len(),
+            Enclosure::Shoelace => Self::count_interior_shoelace(curve),
+        }
+    }
+
+    /// draws the maze with box-drawing characters, the main loop is bold double lines,
+    /// enclosed tiles are a green `I`, and everything outside the loop is dimmed
+    fn render_maze(map: &Map, curve: &HashMap<Coord, i32>, interior: &[Coord]) -> String {
+        let interior = interior.iter().collect::<HashSet<&Coord>>();
+        let width = map.keys().map(|(x, _)| *x).max().unwrap_or(-1) + 1;
+        let height = map.keys().map(|(_, y)| *y).max().unwrap_or(-1) + 1;
+        let mut render = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                let coord = (x, y);
+                let Some(point) = map.get(&coord) else {
+                    continue;
+                };
+                if curve.contains_key(&coord) {
+                    render.push_str(&format!("\x1b[1;33m{}\x1b[0m", point.to_box_char(true)));
+                } else if interior.contains(&coord) {
+                    render.push_str("\x1b[1;32mI\x1b[0m");
+                } else {
+                    render.push_str(&format!("\x1b[2m{}\x1b[0m", point.to_box_char(false)));
                 }
             }
+            render.push('\n');
         }
-        panic!("Couldn't find start direction");
+        render
     }
 
     /// returns the total number of steps taken, and a map of the number of steps taken to each point
     fn traverse_path(start: &Coord, map: &Map) -> (i32, HashMap<Coord, i32>) {
         let mut steps = 0;
-        let (mut current_coord, mut current_point) = Self::find_start_direction(start, map);
         let mut visited = HashMap::new();
-        visited.insert(*start, steps);
+        let mut previous_coord = None;
+        let mut current_coord = *start;
         loop {
-            let checks = vec![
+            visited.insert(current_coord, steps);
+            let current_point = map[&current_coord];
+            let checks = [
                 (current_coord.0, current_coord.1 - 1),
                 (current_coord.0, current_coord.1 + 1),
                 (current_coord.0 - 1, current_coord.1),
                 (current_coord.0 + 1, current_coord.1),
             ];
-            let mut found = false;
-            for check in checks {
-                if let Some(point) = map.get(&check) {
-                    if (!visited.contains_key(&check) || point.is_start())
-                        && current_point
-                            .check_next_point_valid(point, Direction::from((current_coord, check)))
-                    {
-                        steps += 1;
-                        visited.insert(current_coord, steps);
-                        current_coord = check;
-                        current_point = *point;
-                        found = true;
-                        break;
-                    }
-                    if point.is_start() {
-                        found = true;
-                    }
-                }
-            }
-            if !found {
+            let next_coord = checks.into_iter().find(|check| {
+                Some(*check) != previous_coord
+                    && map.get(check).is_some_and(|point| {
+                        current_point
+                            .check_next_point_valid(point, Direction::from((current_coord, *check)))
+                    })
+            });
+            let Some(next_coord) = next_coord else {
+                panic!("Loop is broken at {:?}", current_coord);
+            };
+            steps += 1;
+            if next_coord == *start {
                 break (steps, visited);
             }
+            previous_coord = Some(current_coord);
+            current_coord = next_coord;
+        }
+    }
+}
+
+/// how part 2 counts the tiles enclosed by the loop
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Enclosure {
+    /// walk each row, flipping inside/outside on every north facing pipe
+    Scanline,
+    /// shoelace formula on the ordered loop, then Pick's theorem
+    Shoelace,
+}
+
+impl FromStr for Enclosure {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scanline" => Ok(Enclosure::Scanline),
+            "shoelace" => Ok(Enclosure::Shoelace),
+            _ => Err(format!("unknown enclosure method {s}")),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum StartError {
+    Missing,
+    Connections(usize),
+}
+
+impl Display for StartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartError::Missing => write!(f, "no S in the maze"),
+            StartError::Connections(count) if *count > 2 => {
+                write!(
+                    f,
+                    "S has {count} connected neighbours, the pipe under it is ambiguous"
+                )
+            }
+            StartError::Connections(count) => {
+                write!(
+                    f,
+                    "S has {count} connected neighbours, it can't be on a loop"
+                )
+            }
         }
     }
 }
@@ -155,6 +253,17 @@ enum Direction {
     West,
 }
 
+impl Direction {
+    fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::East => Self::West,
+            Self::West => Self::East,
+        }
+    }
+}
+
 impl From<(Coord, Coord)> for Direction {
     fn from(coords: (Coord, Coord)) -> Self {
         let (start, end) = coords;
@@ -189,8 +298,38 @@ enum Point {
 }
 
 impl Point {
-    fn is_start(&self) -> bool {
-        *self == Point::Start
+    /// whether a pipe has an opening on the given side
+    fn opens_to(&self, direction: Direction) -> bool {
+        match self {
+            Self::NorthSouth => matches!(direction, Direction::North | Direction::South),
+            Self::EastWest => matches!(direction, Direction::East | Direction::West),
+            Self::NorthEast => matches!(direction, Direction::North | Direction::East),
+            Self::NorthWest => matches!(direction, Direction::North | Direction::West),
+            Self::SouthWest => matches!(direction, Direction::South | Direction::West),
+            Self::SouthEast => matches!(direction, Direction::South | Direction::East),
+            // start is replaced by its real pipe while parsing
+            Self::Ground | Self::Start => false,
+        }
+    }
+
+    /// double lines for pipes on the main loop, single lines for the rest
+    fn to_box_char(self, on_loop: bool) -> char {
+        match (self, on_loop) {
+            (Self::NorthSouth, true) => '║',
+            (Self::EastWest, true) => '═',
+            (Self::NorthEast, true) => '╚',
+            (Self::NorthWest, true) => '╝',
+            (Self::SouthWest, true) => '╗',
+            (Self::SouthEast, true) => '╔',
+            (Self::NorthSouth, false) => '│',
+            (Self::EastWest, false) => '─',
+            (Self::NorthEast, false) => '└',
+            (Self::NorthWest, false) => '┘',
+            (Self::SouthWest, false) => '┐',
+            (Self::SouthEast, false) => '┌',
+            (Self::Ground, _) => '.',
+            (Self::Start, _) => 'S',
+        }
     }
 
     /// relative direction is what direction to move from self to other,
@@ -198,9 +337,6 @@ impl Point {
     /// Other
     /// Self
     fn check_next_point_valid(&self, other: &Point, relative_direction: Direction) -> bool {
-        if other.is_start() {
-            return true;
-        }
         match self {
             Self::NorthSouth => match relative_direction {
                 Direction::North => {
@@ -280,29 +416,8 @@ impl Point {
                 }
                 _ => false, // can't go north or west from south east
             },
-            Self::Ground => false,
-            Self::Start => match relative_direction {
-                Direction::North => {
-                    *other == Self::NorthSouth
-                        || *other == Self::SouthWest
-                        || *other == Self::SouthEast
-                }
-                Direction::South => {
-                    *other == Self::NorthSouth
-                        || *other == Self::NorthWest
-                        || *other == Self::NorthEast
-                }
-                Direction::East => {
-                    *other == Self::NorthWest
-                        || *other == Self::EastWest
-                        || *other == Self::SouthWest
-                }
-                Direction::West => {
-                    *other == Self::NorthEast
-                        || *other == Self::EastWest
-                        || *other == Self::SouthEast
-                }
-            },
+            // start is replaced by its real pipe while parsing
+            Self::Ground | Self::Start => false,
         }
     }
 }
@@ -326,18 +441,108 @@ impl From<char> for Point {
 impl Day for Day10 {
     fn part1(&self) -> String {
         let start_time = std::time::Instant::now();
-        let map = self.parse_input();
-        let steps = Self::traverse_path(&map.0, &map.1).0;
+        let (start_point, map) = match self.parse_input() {
+            Ok(parsed) => parsed,
+            Err(err) => return format!("{err}"),
+        };
+        let steps = Self::traverse_path(&start_point, &map).0;
         println!("Part 1 took {:?}", start_time.elapsed());
         format!("{}", (steps + 1) / 2)
     }
 
     fn part2(&self) -> String {
         let start_time = std::time::Instant::now();
-        let (start_point, map) = self.parse_input();
+        let (start_point, map) = match self.parse_input() {
+            Ok(parsed) => parsed,
+            Err(err) => return format!("{err}"),
+        };
         let curve = Self::traverse_path(&start_point, &map).1;
-        let num_interior = Self::find_interior_points(&map, &curve).len();
+        let enclosure = self.params.get("enclosure", Enclosure::Scanline);
+        let num_interior = Self::count_enclosed(&map, &curve, enclosure);
         println!("Part 2 took {:?}", start_time.elapsed());
         format!("{num_interior}")
     }
+
+    fn render(&self) -> Option<String> {
+        let (start_point, map) = match self.parse_input() {
+            Ok(parsed) => parsed,
+            Err(err) => return Some(format!("{err}")),
+        };
+        let curve = Self::traverse_path(&start_point, &map).1;
+        let interior = Self::find_interior_points(&map, &curve);
+        Some(Self::render_maze(&map, &curve, &interior))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the part 2 examples and how many tiles each loop encloses
+    const SAMPLES: [(&str, usize); 3] = [
+        (
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+            4,
+        ),
+        (
+            ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+            8,
+        ),
+        (
+            "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+            10,
+        ),
+    ];
+
+    fn count_enclosed(input: &str, enclosure: Enclosure) -> usize {
+        let day = Day10::new(input.to_string(), Params::default());
+        let (start, map) = day.parse_input().unwrap();
+        let curve = Day10::traverse_path(&start, &map).1;
+        Day10::count_enclosed(&map, &curve, enclosure)
+    }
+
+    #[test]
+    fn scanline_and_shoelace_agree() {
+        for (input, expected) in SAMPLES {
+            assert_eq!(count_enclosed(input, Enclosure::Scanline), expected);
+            assert_eq!(count_enclosed(input, Enclosure::Shoelace), expected);
+        }
+    }
+
+    #[test]
+    fn enclosure_param_picks_the_method() {
+        for method in ["scanline", "shoelace"] {
+            let params = Params::from_iter([("enclosure".to_string(), method.to_string())]);
+            for (input, expected) in SAMPLES {
+                let day = Day10::new(input.to_string(), params.clone());
+                assert_eq!(day.part2(), expected.to_string());
+            }
+        }
+    }
 }
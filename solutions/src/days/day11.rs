@@ -1,16 +1,18 @@
-use std::collections::HashSet;
-
-use super::{get_lines, Day};
+use super::{get_lines, Day, Params};
 
 type Galaxy = (i64, i64);
 
 pub struct Day11 {
     input: String,
+    params: Params,
 }
 
 impl Day11 {
-    pub fn new(input: String) -> Day11 {
-        Day11 { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["expansion1", "expansion2", "pairs"];
+
+    pub fn new(input: String, params: Params) -> Day11 {
+        Day11 { input, params }
     }
 
     fn get_galaxies(&self) -> (Vec<Galaxy>, (Vec<i64>, Vec<i64>)) {
@@ -61,26 +63,61 @@ impl Day11 {
         new_galaxies
     }
 
-    fn distance_between_all_galaxies(galaxies: Vec<Galaxy>) -> i64 {
+    fn distance(galaxy: &Galaxy, other_galaxy: &Galaxy) -> i64 {
+        (galaxy.0 - other_galaxy.0).abs() + (galaxy.1 - other_galaxy.1).abs()
+    }
+
+    /// sum of the distance between every pair of values, once they're sorted each value is
+    /// `value - previous` away from every value before it
+    fn sum_axis_distances(mut values: Vec<i64>) -> i64 {
+        values.sort_unstable();
         let mut total_distance = 0;
-        let mut visited_pairs = HashSet::new();
-        for galaxy in &galaxies {
-            for other_galaxy in &galaxies {
-                if visited_pairs.contains(&(other_galaxy, galaxy)) {
-                    continue;
-                }
-                total_distance +=
-                    (galaxy.0 - other_galaxy.0).abs() + (galaxy.1 - other_galaxy.1).abs();
-                visited_pairs.insert((galaxy, other_galaxy));
-            }
+        let mut prefix_sum = 0;
+        for (idx, value) in values.iter().enumerate() {
+            total_distance += value * idx as i64 - prefix_sum;
+            prefix_sum += value;
         }
         total_distance
     }
+
+    fn distance_between_all_galaxies(galaxies: &[Galaxy]) -> i64 {
+        // manhattan distance splits into independent x and y sums
+        Self::sum_axis_distances(galaxies.iter().map(|galaxy| galaxy.0).collect())
+            + Self::sum_axis_distances(galaxies.iter().map(|galaxy| galaxy.1).collect())
+    }
+
+    /// every unordered pair of galaxies and the distance between them
+    pub fn pair_distances(galaxies: &[Galaxy]) -> impl Iterator<Item = (Galaxy, Galaxy, i64)> + '_ {
+        galaxies.iter().enumerate().flat_map(move |(idx, galaxy)| {
+            galaxies[idx + 1..].iter().map(move |other_galaxy| {
+                (*galaxy, *other_galaxy, Self::distance(galaxy, other_galaxy))
+            })
+        })
+    }
+
+    pub fn nearest_pair(galaxies: &[Galaxy]) -> Option<(Galaxy, Galaxy, i64)> {
+        Self::pair_distances(galaxies).min_by_key(|(_, _, distance)| *distance)
+    }
+
+    /// the farthest pair is always at the extremes of x + y or x - y, so only those are checked
+    pub fn farthest_pair(galaxies: &[Galaxy]) -> Option<(Galaxy, Galaxy, i64)> {
+        let sum_min = galaxies.iter().min_by_key(|galaxy| galaxy.0 + galaxy.1)?;
+        let sum_max = galaxies.iter().max_by_key(|galaxy| galaxy.0 + galaxy.1)?;
+        let diff_min = galaxies.iter().min_by_key(|galaxy| galaxy.0 - galaxy.1)?;
+        let diff_max = galaxies.iter().max_by_key(|galaxy| galaxy.0 - galaxy.1)?;
+        [(sum_min, sum_max), (diff_min, diff_max)]
+            .into_iter()
+            .map(|(galaxy, other_galaxy)| {
+                (*galaxy, *other_galaxy, Self::distance(galaxy, other_galaxy))
+            })
+            .max_by_key(|(_, _, distance)| *distance)
+    }
 }
 
 impl Day for Day11 {
     fn part1(&self) -> String {
-        let expansion_amount = 1;
+        // an empty row becomes `expansion1` rows, so the extra amount is one less
+        let expansion_amount = self.params.get("expansion1", 2) - 1;
         let start = std::time::Instant::now();
         let (galaxies, (galaxy_row_counts, galaxy_column_counts)) = self.get_galaxies();
         let galaxies = Self::adjust_rows_and_columns(
@@ -89,13 +126,19 @@ impl Day for Day11 {
             galaxy_column_counts,
             expansion_amount,
         );
-        let distance = Self::distance_between_all_galaxies(galaxies);
+        let distance = Self::distance_between_all_galaxies(&galaxies);
+        // with `pairs=true` the nearest and farthest galaxies are printed too, finding the
+        // nearest is quadratic so it's off by default
+        if self.params.get("pairs", false) {
+            println!("nearest pair: {:?}", Self::nearest_pair(&galaxies));
+            println!("farthest pair: {:?}", Self::farthest_pair(&galaxies));
+        }
         println!("Part 1 Time: {:?}", start.elapsed());
         format!("{distance}")
     }
 
     fn part2(&self) -> String {
-        let expansion_amount = 999_999;
+        let expansion_amount = self.params.get("expansion2", 1_000_000) - 1;
         let start = std::time::Instant::now();
         let (galaxies, (galaxy_row_counts, galaxy_column_counts)) = self.get_galaxies();
         let galaxies = Self::adjust_rows_and_columns(
@@ -104,7 +147,7 @@ impl Day for Day11 {
             galaxy_column_counts,
             expansion_amount,
         );
-        let distance = Self::distance_between_all_galaxies(galaxies);
+        let distance = Self::distance_between_all_galaxies(&galaxies);
         println!("Part 2 Time: {:?}", start.elapsed());
         format!("{distance}")
     }
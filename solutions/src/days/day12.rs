@@ -1,15 +1,19 @@
-use memoize::memoize;
-use std::{ops::MulAssign, str::FromStr};
+use std::{iter, ops::MulAssign, str::FromStr};
 
-use super::{get_lines, Day};
+use super::{get_lines, Day, Params};
+use crate::utils::parallel::sum_chunks;
 
 pub struct Day12 {
     input: String,
+    params: Params,
 }
 
 impl Day12 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["unfold", "limit"];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
     fn parse_input(&self) -> Vec<Row> {
@@ -18,78 +22,51 @@ impl Day12 {
             .map(|line| line.parse::<Row>().unwrap())
             .collect()
     }
-}
 
-#[memoize]
-fn count_solutions(map: String, counts: Vec<i64>, prev_in_group: Option<i64>) -> i64 {
-    // shoutout https://github.com/fuglede/ for reminding me what dp is
-    let prev_in_group = prev_in_group.unwrap_or(0);
-    if map.is_empty() {
-        // if we closed all groups, string is empty, and counts empty, we got a solution
-        if counts.is_empty() && prev_in_group == 0 {
-            return 1;
-        }
-        return 0;
-    }
-    let mut solutions = 0;
-    let next_chars = if map.starts_with('?') {
-        // want to check for solutions with both '.' and '#'
-        vec!['.', '#']
-    } else {
-        // otherwise, just check for the next char
-        vec![map.chars().next().unwrap()]
-    };
-    for next in next_chars {
-        if next == '#' {
-            // if we have a #, we want to chase that group down
-            solutions += count_solutions(
-                map[1..].to_string(),
-                counts.clone(),
-                Some(prev_in_group + 1),
-            );
-        } else if prev_in_group > 0 {
-            if !counts.is_empty() && counts[0] == prev_in_group {
-                // if we have a '.', we want to close the group if we have one open
-                solutions += count_solutions(map[1..].to_string(), counts[1..].to_vec(), None);
-            }
-        } else {
-            // not in group, move to next
-            solutions += count_solutions(map[1..].to_string(), counts.clone(), None);
-        }
+    /// counts every row, splitting the rows across threads
+    fn count_all_solutions(rows: &[Row]) -> i64 {
+        sum_chunks(rows, |chunk| chunk.iter().map(Row::count_solutions).sum())
     }
-
-    solutions
 }
 
 impl Day for Day12 {
     fn part1(&self) -> String {
         let start_time = std::time::Instant::now();
-        let mut rows = self.parse_input();
-        let total_solutions = rows
-            .iter_mut()
-            .map(|row| {
-                row.map.push('.');
-                count_solutions(row.map.clone(), row.counts.clone(), None)
-            })
-            .sum::<i64>();
+        let rows = self.parse_input();
+        let total_solutions = Self::count_all_solutions(&rows);
         println!("part 1 Time: {:#?}", start_time.elapsed());
         format!("{}", total_solutions)
     }
 
     fn part2(&self) -> String {
         let start_time = std::time::Instant::now();
+        let unfold = self.params.get("unfold", 5);
         let mut rows = self.parse_input();
-        let total_solutions = rows
-            .iter_mut()
-            .map(|row| {
-                *row *= 5;
-                row.map.push('.');
-                count_solutions(row.map.clone(), row.counts.clone(), None)
-            })
-            .sum::<i64>();
+        for row in rows.iter_mut() {
+            *row *= unfold;
+        }
+        let total_solutions = Self::count_all_solutions(&rows);
         println!("part 2 Time: {:#?}", start_time.elapsed());
         format!("{}", total_solutions)
     }
+
+    /// lists the arrangements of every row, at most `limit` (default 20) per row
+    fn render(&self) -> Option<String> {
+        let limit = self.params.get("limit", 20);
+        let mut render = String::new();
+        for row in self.parse_input() {
+            render.push_str(&format!(
+                "{} {:?}: {}\n",
+                row.map,
+                row.counts,
+                row.count_solutions()
+            ));
+            for arrangement in row.arrangements().take(limit) {
+                render.push_str(&format!("    {arrangement}\n"));
+            }
+        }
+        Some(render)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +75,106 @@ struct Row {
     map: String,
 }
 
+impl Row {
+    fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements::new(self)
+    }
+
+    /// dp over (position, group index, run length), only the layer for the current position is
+    /// kept, so the table is `(groups + 1) * (longest group + 1)` cells for the whole row
+    fn count_solutions(&self) -> i64 {
+        let width = self.counts.iter().max().copied().unwrap_or(0) as usize + 1;
+        // ways[group * width + run] is the number of ways to have closed `group` groups and be
+        // `run` springs into the next one
+        let mut ways = vec![0; (self.counts.len() + 1) * width];
+        let mut next_ways = ways.clone();
+        ways[0] = 1;
+        // a trailing '.' closes whatever group is still open at the end
+        for spring in self.map.bytes().chain(iter::once(b'.')) {
+            next_ways.fill(0);
+            for group in 0..=self.counts.len() {
+                for run in 0..width {
+                    let count = ways[group * width + run];
+                    if count == 0 {
+                        continue;
+                    }
+                    if spring != b'.'
+                        && group < self.counts.len()
+                        && (run as i64) < self.counts[group]
+                    {
+                        // extend the current group
+                        next_ways[group * width + run + 1] += count;
+                    }
+                    if spring != b'#' {
+                        if run == 0 {
+                            // not in a group, move to next
+                            next_ways[group * width] += count;
+                        } else if run as i64 == self.counts[group] {
+                            // close the group, it was the right size
+                            next_ways[(group + 1) * width] += count;
+                        }
+                    }
+                }
+            }
+            std::mem::swap(&mut ways, &mut next_ways);
+        }
+        ways[self.counts.len() * width]
+    }
+}
+
+/// lazily walks every `#`/`.` assignment of a row's `?`s that matches its counts, depth first
+struct Arrangements<'a> {
+    row: &'a Row,
+    // partial assignment, closed groups, current run length
+    stack: Vec<(Vec<u8>, usize, usize)>,
+}
+
+impl<'a> Arrangements<'a> {
+    fn new(row: &'a Row) -> Self {
+        Self {
+            row,
+            stack: vec![(Vec::new(), 0, 0)],
+        }
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let counts = &self.row.counts;
+        let map = self.row.map.as_bytes();
+        while let Some((assignment, group, run)) = self.stack.pop() {
+            let Some(spring) = map.get(assignment.len()) else {
+                let closed = (group == counts.len() && run == 0)
+                    || (group + 1 == counts.len() && run as i64 == counts[group]);
+                if closed {
+                    return Some(String::from_utf8(assignment).unwrap());
+                }
+                continue;
+            };
+            // push '#' first so '.' comes out first
+            if *spring != b'.' && group < counts.len() && (run as i64) < counts[group] {
+                let mut next = assignment.clone();
+                next.push(b'#');
+                self.stack.push((next, group, run + 1));
+            }
+            if *spring != b'#' {
+                if run == 0 {
+                    let mut next = assignment;
+                    next.push(b'.');
+                    self.stack.push((next, group, 0));
+                } else if run as i64 == counts[group] {
+                    let mut next = assignment;
+                    next.push(b'.');
+                    self.stack.push((next, group + 1, 0));
+                }
+            }
+        }
+        None
+    }
+}
+
 impl MulAssign<i64> for Row {
     fn mul_assign(&mut self, rhs: i64) {
         let delim = "?";
@@ -133,3 +210,71 @@ impl FromStr for Row {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// the part 1 examples plus a few edge cases, with how many arrangements each has
+    const SAMPLES: [(&str, i64); 9] = [
+        ("???.### 1,1,3", 1),
+        (".??..??...?##. 1,1,3", 4),
+        ("?#?#?#?#?#?#?#? 1,3,1,6", 1),
+        ("????.#...#... 4,1,1", 1),
+        ("????.######..#####. 1,6,5", 4),
+        ("?###???????? 3,2,1", 10),
+        ("????? 1,1", 6),
+        ("#.# 1,1", 1),
+        ("??? 4", 0),
+    ];
+
+    /// every `#`/`.` filling of the `?`s whose damaged runs match the counts
+    fn brute_force(row: &Row) -> HashSet<String> {
+        let unknowns = row.map.matches('?').count();
+        (0..1u32 << unknowns)
+            .map(|mask| {
+                let mut bit = 0;
+                row.map
+                    .chars()
+                    .map(|spring| match spring {
+                        '?' => {
+                            bit += 1;
+                            if mask & (1 << (bit - 1)) != 0 {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        }
+                        _ => spring,
+                    })
+                    .collect::<String>()
+            })
+            .filter(|filled| {
+                let runs = filled
+                    .split('.')
+                    .filter(|run| !run.is_empty())
+                    .map(|run| run.len() as i64)
+                    .collect::<Vec<i64>>();
+                runs == row.counts
+            })
+            .collect()
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        for (line, expected) in SAMPLES {
+            let row = line.parse::<Row>().unwrap();
+            let brute_force = brute_force(&row);
+            assert_eq!(brute_force.len() as i64, expected, "{line}");
+            assert_eq!(row.count_solutions(), expected, "{line}");
+            assert_eq!(
+                row.arrangements().collect::<HashSet<_>>(),
+                brute_force,
+                "{line}"
+            );
+            assert_eq!(row.arrangements().count() as i64, expected, "{line}");
+        }
+    }
+}
@@ -1,204 +1,153 @@
-use std::collections::VecDeque;
-
-use memoize::memoize;
-
-use super::Day;
+use super::{Day, Params};
+use crate::utils::cycle;
 
 pub struct Day14 {
     input: String,
+    params: Params,
 }
 
 impl Day14 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["tilts", "spins", "edge"];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
-    fn get_columns(&self) -> Vec<Vec<char>> {
-        let mut columns = vec![vec![]; self.input.lines().next().unwrap().len()];
-        for line in self.input.lines() {
-            for (i, c) in line.chars().enumerate() {
-                columns[i].push(c);
-            }
-        }
-        columns
+    fn parse_tilts(tilts: &str) -> Vec<Tilt> {
+        tilts
+            .chars()
+            .map(|c| Tilt::try_from(c).unwrap_or_else(|_| panic!("invalid tilt {c}")))
+            .collect()
     }
+}
 
-    fn get_spot_load(idx: usize, len: usize) -> usize {
-        len - idx
+impl Day for Day14 {
+    fn part1(&self) -> String {
+        let start_time = std::time::Instant::now();
+        let mut grid = Grid::from(self.input.as_str());
+        grid.tilt(Tilt::North);
+        let weight = grid.load(Tilt::North);
+        println!("part 1 took {:?}", start_time.elapsed());
+        format!("{weight}")
+    }
+    fn part2(&self) -> String {
+        let start_time = std::time::Instant::now();
+        // a spin is the `tilts` sequence, run `spins` times, and load is measured on `edge`
+        let tilts = Self::parse_tilts(&self.params.get("tilts", "NWSE".to_string()));
+        let spins = self.params.get("spins", 1_000_000_000);
+        let edge = Self::parse_tilts(&self.params.get("edge", "N".to_string()))[0];
+        let grid = Grid::from(self.input.as_str());
+        let grid = grid.run_spins(&tilts, spins);
+        let weight = grid.load(edge);
+        println!("part 2 took {:?}", start_time.elapsed());
+        format!("{weight}")
     }
+}
 
-    fn get_col_weight(col: Vec<char>) -> usize {
-        // we assume that we want the weight directed at whatever the front is, for part 1
-        // the front will be north
-        // this will be a queue of open indicies, to keep track of where boulders can roll
-        let mut weight = 0;
-        let mut open_spots = VecDeque::new();
-        let col_len = col.len();
-        for (idx, rock) in col.iter().enumerate() {
-            match rock {
-                '.' => {
-                    open_spots.push_back(idx);
-                }
-                'O' => {
-                    // if there is an empty spot, the boulder rolls to the first one, so we get
-                    // the weight of that spot
-                    if let Some(open_idx) = open_spots.pop_front() {
-                        weight += Self::get_spot_load(open_idx, col_len);
-                        // this boulder rolled, so its spot is now open
-                        open_spots.push_back(idx);
-                    } else {
-                        // no empty spots, so calculate in place
-                        weight += Self::get_spot_load(idx, col_len);
-                    }
-                }
-                '#' => {
-                    // this boulder can't move, and also clears all open spots in front of it
-                    open_spots.clear();
-                }
-                _ => panic!("invalid"),
-            }
-        }
+/// the direction the platform is tilted, which is also the edge rocks roll towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tilt {
+    North,
+    West,
+    South,
+    East,
+}
 
-        weight
-    }
+impl TryFrom<char> for Tilt {
+    type Error = ();
 
-    fn move_rocks(col: Vec<char>) -> Vec<char> {
-        // this can work for cols and also rows
-        let mut new_col = Vec::new();
-        let mut open_spots = VecDeque::new();
-        for (idx, rock) in col.iter().enumerate() {
-            match rock {
-                '.' => {
-                    open_spots.push_back(idx);
-                    new_col.push('.');
-                }
-                'O' => {
-                    // if there is an empty spot, the boulder rolls to the first one, so we get
-                    // the weight of that spot
-                    if let Some(open_idx) = open_spots.pop_front() {
-                        new_col[open_idx] = 'O';
-                        // this boulder rolled, so its spot is now open
-                        open_spots.push_back(idx);
-                        new_col.push('.');
-                    } else {
-                        // no empty spots, so put it here
-                        new_col.push('O');
-                    }
-                }
-                '#' => {
-                    // this boulder can't move, and also clears all open spots in front of it
-                    open_spots.clear();
-                    new_col.push('#');
-                }
-                _ => panic!("invalid"),
-            }
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'N' => Ok(Tilt::North),
+            'W' => Ok(Tilt::West),
+            'S' => Ok(Tilt::South),
+            'E' => Ok(Tilt::East),
+            _ => Err(()),
         }
-        new_col
     }
 }
 
-#[memoize]
-fn get_col_weight_in_place(col: Vec<char>) -> usize {
-    let mut weight = 0;
-    let col_len = col.len();
-    for (idx, rock) in col.iter().enumerate() {
-        if rock == &'O' {
-            weight += Day14::get_spot_load(idx, col_len);
-        }
-    }
-    weight
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Grid {
+    width: usize,
+    height: usize,
+    // row major, `O` is a round rock, `#` a cube rock, `.` empty
+    cells: Vec<u8>,
 }
 
-#[memoize]
-fn do_spin_cycle(cols: Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut new_cols = Vec::new();
-    // we start with cols north-south
-    for col in cols {
-        new_cols.push(Day14::move_rocks(col));
-    }
-    // now we tanspose the cols to be west-east
-    let mut we_cols = vec![vec![]; new_cols[0].len()];
-    for col in new_cols {
-        for (i, c) in col.iter().enumerate() {
-            we_cols[i].push(*c);
-        }
-    }
-    // move again
-    let mut new_cols = Vec::new();
-    for col in we_cols {
-        new_cols.push(Day14::move_rocks(col));
-    }
-    // transpose back to north south, but with south at the beginning now
-    let mut sn_cols = vec![vec![]; new_cols[0].len()];
-    // going backwards to do south to north
-    for col in new_cols.iter().rev() {
-        for (i, c) in col.iter().enumerate() {
-            sn_cols[i].push(*c);
+impl Grid {
+    /// rolls every round rock as far as it goes towards the `tilt` edge
+    fn tilt(&mut self, tilt: Tilt) {
+        let (width, height) = (self.width, self.height);
+        // a lane is a column for north/south and a row for west/east, walked from the edge inwards
+        let (num_lanes, lane_len) = match tilt {
+            Tilt::North | Tilt::South => (width, height),
+            Tilt::West | Tilt::East => (height, width),
+        };
+        for lane in 0..num_lanes {
+            let idx = |pos: usize| match tilt {
+                Tilt::North => pos * width + lane,
+                Tilt::South => (height - 1 - pos) * width + lane,
+                Tilt::West => lane * width + pos,
+                Tilt::East => lane * width + width - 1 - pos,
+            };
+            // the closest spot to the edge a rock can roll to
+            let mut open_pos = 0;
+            for pos in 0..lane_len {
+                match self.cells[idx(pos)] {
+                    b'#' => open_pos = pos + 1,
+                    b'O' => {
+                        self.cells[idx(pos)] = b'.';
+                        self.cells[idx(open_pos)] = b'O';
+                        open_pos += 1;
+                    }
+                    _ => {}
+                }
+            }
         }
     }
-    // move south
-    let mut new_cols = Vec::new();
-    for col in sn_cols {
-        new_cols.push(Day14::move_rocks(col));
-    }
-    // transpose to east-west
-    let mut ew_cols = vec![vec![]; new_cols[0].len()];
-    // going backwards to do east to west
-    for col in new_cols.iter().rev() {
-        for (i, c) in col.iter().enumerate() {
-            ew_cols[i].push(*c);
+
+    /// a copy of the grid after tilting in each direction of `tilts` in order
+    fn spun(&self, tilts: &[Tilt]) -> Grid {
+        let mut grid = self.clone();
+        for tilt in tilts {
+            grid.tilt(*tilt);
         }
+        grid
     }
-    // last roll
-    let mut new_cols = Vec::new();
-    for col in ew_cols {
-        new_cols.push(Day14::move_rocks(col));
-    }
-    // transpose back to north-south, so both are reverse
-    let mut ns_cols = vec![vec![]; new_cols[0].len()];
-    for col in new_cols.iter().rev() {
-        for (i, c) in col.iter().rev().enumerate() {
-            ns_cols[i].push(*c);
-        }
+
+    /// runs `tilts` in order `spins` times, skipping ahead once the grid starts repeating
+    fn run_spins(self, tilts: &[Tilt], spins: usize) -> Grid {
+        cycle::fast_forward(self, |grid| grid.spun(tilts), spins)
     }
-    ns_cols
-}
 
-impl Day for Day14 {
-    fn part1(&self) -> String {
-        let start_time = std::time::Instant::now();
-        // once we have columns, calculate the load in each one then sum them
-        let weight: usize = self
-            .get_columns()
+    /// each round rock weighs as many rows (or columns) as it is away from the far side of `edge`
+    fn load(&self, edge: Tilt) -> usize {
+        self.cells
             .iter()
-            .map(|col| Self::get_col_weight(col.clone()))
-            .sum();
-        println!("part 1 took {:?}", start_time.elapsed());
-        format!("{weight}")
+            .enumerate()
+            .filter(|(_, cell)| **cell == b'O')
+            .map(|(idx, _)| {
+                let (x, y) = (idx % self.width, idx / self.width);
+                match edge {
+                    Tilt::North => self.height - y,
+                    Tilt::South => y + 1,
+                    Tilt::West => self.width - x,
+                    Tilt::East => x + 1,
+                }
+            })
+            .sum()
     }
-    fn part2(&self) -> String {
-        let start_time = std::time::Instant::now();
-        // cols facing north/south
-        let mut ns_cols = self.get_columns();
-        // use this to detect cycles
-        let mut hist_vec: Vec<Vec<Vec<char>>> = Vec::new();
-        let iters = 1_000_000_000;
-        for _ in 0..iters {
-            ns_cols = do_spin_cycle(ns_cols);
-            if let Some(first_idx) = hist_vec.iter().position(|x| *x == ns_cols) {
-                let cycle_len = hist_vec.len() - first_idx;
-                let cycle_iter = ((iters - first_idx) % cycle_len + first_idx) - 1;
-                ns_cols = hist_vec[cycle_iter].clone();
-                break;
-            } else {
-                hist_vec.push(ns_cols.clone());
-            }
+}
+
+impl From<&str> for Grid {
+    fn from(input: &str) -> Self {
+        let lines = input.lines().collect::<Vec<&str>>();
+        Grid {
+            width: lines[0].len(),
+            height: lines.len(),
+            cells: lines.iter().flat_map(|line| line.bytes()).collect(),
         }
-        let weight = ns_cols
-            .iter()
-            .map(|col| get_col_weight_in_place(col.clone()))
-            .sum::<usize>();
-        println!("part 2 took {:?}", start_time.elapsed());
-        format!("{weight}")
     }
 }
@@ -1,14 +1,18 @@
-use std::collections::HashMap;
+use std::fmt::Display;
 
-use super::Day;
+use super::{Day, Params};
 
 pub struct Day15 {
     input: String,
+    params: Params,
 }
 
 impl Day15 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["trace"];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
     fn parse_input(&self) -> String {
@@ -24,18 +28,6 @@ impl Day15 {
         }
         curr_val
     }
-
-    fn score_boxes(box_map: &HashMap<usize, HashMap<&str, (usize, usize)>>) -> usize {
-        // lens score = (box # + 1) * index * len
-        let mut total_score = 0;
-        for (box_num, lenses) in box_map {
-            for (idx, len) in lenses.values() {
-                let score = (box_num + 1) * (idx + 1) * len;
-                total_score += score;
-            }
-        }
-        total_score
-    }
 }
 
 impl Day for Day15 {
@@ -50,40 +42,85 @@ impl Day for Day15 {
     }
 
     fn part2(&self) -> String {
-        // map of box #: HashMap<group,(idx, len)>
         let start_time = std::time::Instant::now();
-        let mut box_map = HashMap::new();
+        // with `trace=true` the boxes are printed after every step
+        let trace = self.params.get("trace", false);
+        let mut boxes = LensBoxes::new();
         let binding = self.parse_input();
         for group in binding.split(',') {
-            if group.contains('-') {
+            if let Some(label) = group.strip_suffix('-') {
                 // take that lens out
-                let group = group.trim_end_matches('-');
-                let entry: &mut HashMap<&str, (usize, usize)> = box_map
-                    .entry(Self::score_group(group))
-                    .or_insert(HashMap::new());
-                if let Some(inner_entry) = entry.remove(group) {
-                    // there was an entry, so decrease all indicies that were greater than this one
-                    for (_, (idx, _)) in entry.iter_mut() {
-                        if *idx > inner_entry.0 {
-                            *idx -= 1;
-                        }
-                    }
-                }
+                boxes.remove(label);
             } else {
-                // add to box map
                 let mut split = group.split('=');
-                let group = split.next().unwrap();
-                let len = split.next().unwrap().parse::<usize>().unwrap();
-                let entry: &mut HashMap<&str, (usize, usize)> = box_map
-                    .entry(Self::score_group(group))
-                    .or_insert(HashMap::new());
-                let entry_len = entry.len();
-                let inner_entry = entry.entry(group).or_insert((entry_len, len));
-                inner_entry.1 = len;
+                let label = split.next().unwrap();
+                let focal_length = split.next().unwrap().parse::<usize>().unwrap();
+                boxes.insert(label, focal_length);
+            }
+            if trace {
+                println!("After \"{group}\":\n{boxes}");
             }
         }
-        let sum = Self::score_boxes(&box_map);
+        let sum = boxes.focusing_power();
         println!("part 2 time: {:?}", start_time.elapsed());
         format!("{sum}")
     }
 }
+
+/// 256 boxes, each holding lenses as (label, focal length) in the order they were put in
+struct LensBoxes<'a> {
+    boxes: Vec<Vec<(&'a str, usize)>>,
+}
+
+impl<'a> LensBoxes<'a> {
+    fn new() -> Self {
+        Self {
+            boxes: vec![Vec::new(); 256],
+        }
+    }
+
+    /// swaps the focal length if the label is already in its box, otherwise adds it to the back
+    fn insert(&mut self, label: &'a str, focal_length: usize) {
+        let lenses = &mut self.boxes[Day15::score_group(label)];
+        match lenses.iter_mut().find(|(lens, _)| *lens == label) {
+            Some(lens) => lens.1 = focal_length,
+            None => lenses.push((label, focal_length)),
+        }
+    }
+
+    /// takes the lens out, everything behind it moves forward
+    fn remove(&mut self, label: &str) {
+        self.boxes[Day15::score_group(label)].retain(|(lens, _)| *lens != label);
+    }
+
+    fn focusing_power(&self) -> usize {
+        // lens score = (box # + 1) * (slot # + 1) * focal length
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(box_num, lenses)| {
+                lenses
+                    .iter()
+                    .enumerate()
+                    .map(move |(slot, (_, focal_length))| (box_num + 1) * (slot + 1) * focal_length)
+            })
+            .sum()
+    }
+}
+
+impl Display for LensBoxes<'_> {
+    /// only boxes with lenses in them, like `Box 3: [ot 7] [ab 5] [pc 6]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (box_num, lenses) in self.boxes.iter().enumerate() {
+            if lenses.is_empty() {
+                continue;
+            }
+            write!(f, "Box {box_num}:")?;
+            for (label, focal_length) in lenses {
+                write!(f, " [{label} {focal_length}]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
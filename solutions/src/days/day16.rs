@@ -1,14 +1,21 @@
-use std::collections::{HashSet, VecDeque};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
 
-use super::{get_lines, Day};
+use super::{get_lines, Day, Params};
 
 pub struct Day16 {
     input: String,
+    params: Params,
 }
 
 impl Day16 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["entry_x", "entry_y", "entry_dir"];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
     fn parse_input(&self) -> Vec<Vec<char>> {
@@ -18,18 +25,41 @@ impl Day16 {
             .collect()
     }
 
-    fn visualize_energized(&self, energized_tiles: &HashSet<(usize, usize)>) {
-        let map = self.parse_input();
+    /// the mirror layout with the beams from one entry point drawn over the empty tiles, an arrow
+    /// where one beam passed and the number of beams where several crossed
+    fn render_beams(map: &[Vec<char>], visited: &HashSet<(Direction, (usize, usize))>) -> String {
+        let mut beams: HashMap<(usize, usize), Vec<Direction>> = HashMap::new();
+        for (dir, square) in visited {
+            beams.entry(*square).or_default().push(*dir);
+        }
+        let mut render = String::new();
         for (y, row) in map.iter().enumerate() {
-            for (x, _col) in row.iter().enumerate() {
-                if energized_tiles.contains(&(x, y)) {
-                    print!("#");
-                } else {
-                    print!(".");
+            for (x, tile) in row.iter().enumerate() {
+                match (tile, beams.get(&(x, y))) {
+                    ('.', Some(dirs)) if dirs.len() == 1 => render.push(dirs[0].arrow()),
+                    ('.', Some(dirs)) => render.push_str(&dirs.len().to_string()),
+                    _ => render.push(*tile),
                 }
             }
-            println!();
+            render.push('\n');
         }
+        render
+    }
+
+    /// every square on the edge, with the direction pointing into the grid
+    fn entry_points(map: &[Vec<char>]) -> Vec<(Direction, (usize, usize))> {
+        let max_x = map[0].len() - 1;
+        let max_y = map.len() - 1;
+        let mut entry_points = Vec::new();
+        for x in 0..=max_x {
+            entry_points.push((Direction::Down, (x, 0)));
+            entry_points.push((Direction::Up, (x, max_y)));
+        }
+        for y in 0..=max_y {
+            entry_points.push((Direction::Right, (0, y)));
+            entry_points.push((Direction::Left, (max_x, y)));
+        }
+        entry_points
     }
 
     fn get_next_square(
@@ -72,19 +102,28 @@ impl Day16 {
     }
 
     fn count_energized_tiles(
-        &self,
         start_dir: Direction,
         start_square: (usize, usize),
-        map: &Vec<Vec<char>>,
+        map: &[Vec<char>],
     ) -> usize {
-        // start count at 1 for the starting tile
+        Self::trace_beams(start_dir, start_square, map)
+            .iter()
+            .map(|(_, square)| *square)
+            .collect::<HashSet<(usize, usize)>>()
+            .len()
+    }
+
+    /// every (direction, square) a beam passes through
+    fn trace_beams(
+        start_dir: Direction,
+        start_square: (usize, usize),
+        map: &[Vec<char>],
+    ) -> HashSet<(Direction, (usize, usize))> {
         let mut curr_dir = start_dir;
         let (mut x, mut y) = start_square;
-        let mut energized_tiles = HashSet::new();
         let mut visited = HashSet::new();
         let mut queue = VecDeque::new();
         queue.push_back((curr_dir, (x, y)));
-        energized_tiles.insert((x, y));
         while let Some((dir, square)) = queue.pop_front() {
             if visited.contains(&(dir, square)) {
                 continue;
@@ -235,16 +274,8 @@ impl Day16 {
                 }
                 _ => panic!("Invalid character in map"),
             }
-            if !energized_tiles.contains(&(x, y)) {
-                energized_tiles.insert((x, y));
-            }
         }
-        cfg_if::cfg_if! {
-            if #[cfg(debug_assertions)] {
-            self.visualize_energized(&energized_tiles);
-            }
-        }
-        energized_tiles.len()
+        visited
     }
 }
 
@@ -252,7 +283,7 @@ impl Day for Day16 {
     fn part1(&self) -> String {
         let start_time = std::time::Instant::now();
         let energized_tiles =
-            self.count_energized_tiles(Direction::Right, (0, 0), &self.parse_input());
+            Self::count_energized_tiles(Direction::Right, (0, 0), &self.parse_input());
         println!("part1 time: {:?}", start_time.elapsed());
         format!("{energized_tiles}")
     }
@@ -261,41 +292,245 @@ impl Day for Day16 {
         let start_time = std::time::Instant::now();
         // try every position around the perimiter, starting in, and find the max
         let map = self.parse_input();
-        let max_x = map[0].len() - 1;
-        let max_y = map.len() - 1;
-        // score and starting position
-        let mut max_energized = (0, (0, 0));
-        for x in 0..=max_x {
-            for y in 0..=max_y {
-                if x == 0 {
-                    let energized = self.count_energized_tiles(Direction::Right, (x, y), &map);
-                    if energized > max_energized.0 {
-                        max_energized = (energized, (x, y));
-                    }
+        let graph = BeamGraph::new(&map);
+        let max_energized = Self::entry_points(&map)
+            .into_iter()
+            .map(|(dir, square)| graph.count_energized(dir, square))
+            .max()
+            .unwrap_or(0);
+        println!("part2 time: {:?}", start_time.elapsed());
+        format!("{max_energized}")
+    }
+
+    /// beams from `entry_x`, `entry_y` heading `entry_dir` (one of `UDLR`), top left going right
+    /// by default
+    fn render(&self) -> Option<String> {
+        let map = self.parse_input();
+        let square = (self.params.get("entry_x", 0), self.params.get("entry_y", 0));
+        let dir = self.params.get("entry_dir", Direction::Right);
+        Some(Self::render_beams(
+            &map,
+            &Self::trace_beams(dir, square, &map),
+        ))
+    }
+}
+
+/// a set of tiles as one bit per tile, so unions are cheap
+#[derive(Debug, Clone)]
+struct TileSet {
+    width: usize,
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            bits: vec![0; (width * height).div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, (x, y): (usize, usize)) {
+        let idx = y * self.width + x;
+        self.bits[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn union_with(&mut self, other: &TileSet) {
+        for (bits, other_bits) in self.bits.iter_mut().zip(&other.bits) {
+            *bits |= other_bits;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// The contraption compiled into the beam segments that run between splitters.
+///
+/// Each splitter a beam hits side-on is a node, its two outgoing beams light up tiles until they
+/// hit the next splitters side-on (the node's edges) or leave the grid. Every tile lit once a
+/// beam reaches a node is the same no matter where the beam came from, so it's worked out once
+/// per strongly connected component and shared.
+struct BeamGraph<'a> {
+    map: &'a [Vec<char>],
+    nodes: HashMap<(usize, usize), usize>,
+    // per node, every tile lit once a beam reaches it
+    reachable: Vec<TileSet>,
+}
+
+impl<'a> BeamGraph<'a> {
+    fn new(map: &'a [Vec<char>]) -> Self {
+        let mut graph = Self {
+            map,
+            nodes: HashMap::new(),
+            reachable: Vec::new(),
+        };
+        for (y, row) in map.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if *tile == '-' || *tile == '|' {
+                    graph.nodes.insert((x, y), graph.nodes.len());
                 }
-                if y == 0 {
-                    let energized = self.count_energized_tiles(Direction::Down, (x, y), &map);
-                    if energized > max_energized.0 {
-                        max_energized = (energized, (x, y));
+            }
+        }
+
+        let mut positions = vec![(0, 0); graph.nodes.len()];
+        for (position, node) in &graph.nodes {
+            positions[*node] = *position;
+        }
+        let mut own_tiles = Vec::new();
+        let mut edges = Vec::new();
+        for position in &positions {
+            let mut tiles = TileSet::new(map[0].len(), map.len());
+            tiles.insert(*position);
+            let mut next_nodes = Vec::new();
+            let split = match map[position.1][position.0] {
+                '-' => [Direction::Left, Direction::Right],
+                _ => [Direction::Up, Direction::Down],
+            };
+            for dir in split {
+                if let Some(square) = graph.next_square(*position, dir) {
+                    let (segment, next_node) = graph.trace_segment(dir, square);
+                    tiles.union_with(&segment);
+                    next_nodes.extend(next_node);
+                }
+            }
+            own_tiles.push(tiles);
+            edges.push(next_nodes);
+        }
+
+        // tarjan hands back components with everything they lead to already done, so each one
+        // is its own tiles plus the components it points at
+        let components = strongly_connected_components(&edges);
+        let mut component_of = vec![0; positions.len()];
+        for (idx, component) in components.iter().enumerate() {
+            for node in component {
+                component_of[*node] = idx;
+            }
+        }
+        let mut component_tiles: Vec<TileSet> = Vec::new();
+        for (idx, component) in components.iter().enumerate() {
+            let mut tiles = TileSet::new(map[0].len(), map.len());
+            for node in component {
+                tiles.union_with(&own_tiles[*node]);
+                for next_node in &edges[*node] {
+                    if component_of[*next_node] != idx {
+                        tiles.union_with(&component_tiles[component_of[*next_node]]);
                     }
                 }
-                if x == max_x {
-                    let energized = self.count_energized_tiles(Direction::Left, (x, y), &map);
-                    if energized > max_energized.0 {
-                        max_energized = (energized, (x, y));
+            }
+            component_tiles.push(tiles);
+        }
+        graph.reachable = component_of
+            .iter()
+            .map(|component| component_tiles[*component].clone())
+            .collect();
+        graph
+    }
+
+    fn next_square(&self, (x, y): (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        Day16::get_next_square(x, y, dir, self.map[0].len() - 1, self.map.len() - 1)
+    }
+
+    /// follows a beam until it hits a splitter side-on or leaves the grid, returning the tiles
+    /// it lit and the splitter node it hit
+    fn trace_segment(&self, mut dir: Direction, start: (usize, usize)) -> (TileSet, Option<usize>) {
+        let mut tiles = TileSet::new(self.map[0].len(), self.map.len());
+        let mut visited = HashSet::new();
+        let mut square = start;
+        while visited.insert((dir, square)) {
+            tiles.insert(square);
+            dir = match (self.map[square.1][square.0], dir) {
+                ('-', Direction::Up | Direction::Down)
+                | ('|', Direction::Left | Direction::Right) => {
+                    return (tiles, Some(self.nodes[&square]));
+                }
+                ('/', Direction::Up) | ('\\', Direction::Down) => Direction::Right,
+                ('/', Direction::Down) | ('\\', Direction::Up) => Direction::Left,
+                ('/', Direction::Left) | ('\\', Direction::Right) => Direction::Down,
+                ('/', Direction::Right) | ('\\', Direction::Left) => Direction::Up,
+                _ => dir,
+            };
+            match self.next_square(square, dir) {
+                Some(next_square) => square = next_square,
+                None => break,
+            }
+        }
+        (tiles, None)
+    }
+
+    fn count_energized(&self, dir: Direction, start: (usize, usize)) -> usize {
+        let (mut tiles, node) = self.trace_segment(dir, start);
+        if let Some(node) = node {
+            tiles.union_with(&self.reachable[node]);
+        }
+        tiles.len()
+    }
+}
+
+/// tarjan's algorithm, components come out in reverse topological order
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.low_link[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for next_node in self.edges[node].clone() {
+                match self.index[next_node] {
+                    None => {
+                        self.visit(next_node);
+                        self.low_link[node] = self.low_link[node].min(self.low_link[next_node]);
+                    }
+                    Some(next_index) if self.on_stack[next_node] => {
+                        self.low_link[node] = self.low_link[node].min(next_index);
                     }
+                    _ => {}
                 }
-                if y == max_y {
-                    let energized = self.count_energized_tiles(Direction::Up, (x, y), &map);
-                    if energized > max_energized.0 {
-                        max_energized = (energized, (x, y));
+            }
+            if Some(self.low_link[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
                     }
                 }
+                self.components.push(component);
             }
         }
-        println!("part2 time: {:?}", start_time.elapsed());
-        format!("{:?}", max_energized.0)
     }
+
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; edges.len()],
+        low_link: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
 }
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Copy)]
@@ -305,3 +540,64 @@ enum Direction {
     Left,
     Right,
 }
+
+impl Direction {
+    fn arrow(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            _ => Err(format!("unknown direction {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn sample() {
+        let day = Day16::new(SAMPLE.to_string(), Params::default());
+        assert_eq!(day.part1(), "46");
+        assert_eq!(day.part2(), "51");
+    }
+
+    #[test]
+    fn beam_graph_matches_bfs_from_every_entry() {
+        let map = Day16::new(SAMPLE.to_string(), Params::default()).parse_input();
+        let graph = BeamGraph::new(&map);
+        for (dir, square) in Day16::entry_points(&map) {
+            assert_eq!(
+                graph.count_energized(dir, square),
+                Day16::count_energized_tiles(dir, square, &map),
+                "from {square:?} going {dir:?}"
+            );
+        }
+    }
+}
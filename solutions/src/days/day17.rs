@@ -1,256 +1,287 @@
-use std::collections::HashMap;
-
-use num::integer::sqrt;
-
-use super::{get_lines, Day};
+use super::{get_lines, Day, Params};
 
 pub struct Day17 {
     input: String,
+    params: Params,
 }
 
 impl Day17 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &[
+        "part", "min_run1", "max_run1", "min_run2", "max_run2", "format",
+    ];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
-    fn parse_input(&self) -> HashMap<(usize, usize), Pos> {
+    fn parse_input(&self) -> HeatMap {
         let lines = get_lines(&self.input);
-        let mut points = HashMap::new();
-        for y in 0..lines.len() {
-            for x in 0..lines[0].len() {
-                points.insert(
-                    (x, y),
-                    Pos {
-                        x,
-                        y,
-                        heat_loss: lines[y].chars().nth(x).unwrap().to_digit(10).unwrap() as usize,
-                    },
-                );
-            }
+        HeatMap {
+            width: lines[0].len(),
+            height: lines.len(),
+            heat_loss: lines
+                .iter()
+                .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap() as usize))
+                .collect(),
         }
-        points
     }
 
-    fn get_successors(
-        point: &Direction,
-        map: &HashMap<(usize, usize), Pos>,
-        min_path: u8,
-        max_path: u8,
-        goal: (usize, usize),
-    ) -> Vec<(Direction, usize)> {
-        // return all valid successors, removing ones that go too far in the direction
-        let mut successors = vec![];
-        if point.pos.x > 0 && point.delta.0 != 1 {
-            // only valid if we can keep going in this direction, we also don't want to go directly backwards
-            if (point.delta.0 == -1 && point.count <= max_path) || point.delta.0 == 0 {
-                let curr_point = map.get(&(point.pos.x - 1, point.pos.y)).unwrap();
-                let item = (
-                    Direction {
-                        pos: curr_point.clone(),
-                        delta: (-1, 0),
-                        count: if point.delta.0 == -1 {
-                            point.count + 1
-                        } else {
-                            1
-                        },
-                    },
-                    curr_point.heat_loss,
-                );
-                if item.0.pos.x == goal.0
-                    && item.0.pos.y == goal.1
-                    && item.0.count >= min_path
-                    && item.0.count <= max_path
-                {
-                    // we've reached the goal, return the path
-                    return vec![item];
-                }
-                successors.push(item);
-            }
-        }
-        if point.pos.y > 0 && point.delta.1 != 1 {
-            // only valid if we can keep going in this direction, we also don't want to go directly backwards
-            if (point.delta.1 == -1 && point.count <= max_path) || point.delta.1 == 0 {
-                let curr_point = map.get(&(point.pos.x, point.pos.y - 1)).unwrap();
-                let item = (
-                    Direction {
-                        pos: curr_point.clone(),
-                        delta: (0, -1),
-                        count: if point.delta.1 == -1 {
-                            point.count + 1
-                        } else {
-                            1
-                        },
-                    },
-                    curr_point.heat_loss,
-                );
-                if item.0.pos.x == goal.0
-                    && item.0.pos.y == goal.1
-                    && item.0.count >= min_path
-                    && item.0.count <= max_path
-                {
-                    // we've reached the goal, return the path
-                    return vec![item];
-                }
-                successors.push(item);
-            }
-        }
-        if point.pos.x < goal.0 && point.delta.0 != -1 {
-            // only valid if we can keep going in this direction, we also don't want to go directly backwards
-            if (point.delta.0 == 1 && point.count <= max_path) || point.delta.0 == 0 {
-                let curr_point = map.get(&(point.pos.x + 1, point.pos.y)).unwrap();
-                let item = (
-                    Direction {
-                        pos: curr_point.clone(),
-                        delta: (1, 0),
-                        count: if point.delta.0 == 1 {
-                            point.count + 1
-                        } else {
-                            1
-                        },
-                    },
-                    curr_point.heat_loss,
-                );
-                if item.0.pos.x == goal.0
-                    && item.0.pos.y == goal.1
-                    && item.0.count >= min_path
-                    && item.0.count <= max_path
-                {
-                    // we've reached the goal, return the path
-                    return vec![item];
-                }
-                successors.push(item);
-            }
-        }
-        if point.pos.y < goal.1 && point.delta.1 != -1 {
-            // only valid if we can keep going in this direction, we also don't want to go directly backwards
-            if (point.delta.1 == 1 && point.count <= max_path) || point.delta.1 == 0 {
-                let curr_point = map.get(&(point.pos.x, point.pos.y + 1)).unwrap();
-                let item = (
-                    Direction {
-                        pos: curr_point.clone(),
-                        delta: (0, 1),
-                        count: if point.delta.1 == 1 {
-                            point.count + 1
-                        } else {
-                            1
-                        },
-                    },
-                    curr_point.heat_loss,
+    fn solve(map: &HeatMap, min_run: usize, max_run: usize) -> String {
+        let Some(route) = map.find_route(min_run, max_run) else {
+            return "no route".to_string();
+        };
+        cfg_if::cfg_if! {
+            if #[cfg(debug_assertions)] {
+                let runs = route.runs();
+                assert!(
+                    runs.iter().all(|run| (min_run..=max_run).contains(run)),
+                    "route breaks the run limits: {runs:?}"
                 );
-                if item.0.pos.x == goal.0
-                    && item.0.pos.y == goal.1
-                    && item.0.count >= min_path
-                    && item.0.count <= max_path
-                {
-                    // we've reached the goal, return the path
-                    return vec![item];
-                }
-                successors.push(item);
             }
         }
-        successors
-            .iter()
-            .filter(|s| s.0.count <= max_path)
-            .filter(|s| {
-                // if it is the same, need the right count, if it is different, don't need it
-                s.0.delta != point.delta || s.0.count >= min_path
-            })
-            .filter(|s| !(s.0.pos.x == goal.0 && s.0.pos.y == goal.1 && s.0.count < min_path))
-            .map(|x| x.to_owned())
-            .collect()
-    }
-
-    fn get_next_successor_in_path(
-        point: &Direction,
-        map: &HashMap<(usize, usize), Pos>,
-        min_path: u8,
-        max_path: u8,
-        goal: (usize, usize),
-    ) -> Vec<(Direction, usize)> {
-        let Some(pos) = map
-            .get(&(
-                (point.pos.x as i64 + point.delta.0 as i64) as usize,
-                (point.pos.y as i64 + point.delta.1 as i64) as usize,
-            ))
-            .cloned()
-        else {
-            return vec![];
-        };
-        let heat_loss = pos.heat_loss;
-        let next = Direction {
-            pos,
-            delta: point.delta,
-            count: point.count + 1,
-        };
-        if next.pos.x == goal.0
-            && next.pos.y == goal.1
-            && (next.count < min_path || next.count > max_path)
-        {
-            // we dont have enough on the path to finish
-            return vec![];
-        }
-        vec![(next, heat_loss)]
-    }
-
-    fn do_astar(
-        map: HashMap<(usize, usize), Pos>,
-        goal: (usize, usize),
-        min_path: u8,
-        max_path: u8,
-    ) -> usize {
-        let init_dir = Direction {
-            pos: map.get(&(0, 0)).cloned().unwrap(),
-            delta: (0, 0),
-            count: 0,
-        };
-        let path = pathfinding::directed::astar::astar(
-            &init_dir,
-            |p| {
-                if p.count < min_path && p.delta != (0, 0) {
-                    // need to get next one in path
-                    Self::get_next_successor_in_path(p, &map, min_path, max_path, goal)
-                } else {
-                    Self::get_successors(p, &map, min_path, max_path, goal)
-                }
-            },
-            |p| goal.0.abs_diff(p.pos.x) + goal.1.abs_diff(p.pos.y),
-            |p| p.pos == *map.get(&goal).unwrap(),
-        );
-
-        path.unwrap().1
+        format!("{}", route.heat_loss)
     }
 }
 
 impl Day for Day17 {
     fn part1(&self) -> String {
         let start_time = std::time::Instant::now();
-        let points = self.parse_input();
-        let side_len = sqrt(points.len()) - 1;
-        let heat = Self::do_astar(points, (side_len, side_len), 1, 3);
+        let map = self.parse_input();
+        let min_run = self.params.get("min_run1", 1);
+        let max_run = self.params.get("max_run1", 3);
+        let heat = Self::solve(&map, min_run, max_run);
         println!("Part 1 took {:?}", start_time.elapsed());
-        format!("{heat}")
+        heat
     }
 
     fn part2(&self) -> String {
         let start_time = std::time::Instant::now();
-        let points = self.parse_input();
-        let side_len = sqrt(points.len()) - 1;
-        let heat = Self::do_astar(points, (side_len, side_len), 4, 10);
+        let map = self.parse_input();
+        let min_run = self.params.get("min_run2", 4);
+        let max_run = self.params.get("max_run2", 10);
+        let heat = Self::solve(&map, min_run, max_run);
         println!("Part 2 took {:?}", start_time.elapsed());
-        format!("{heat}")
+        heat
+    }
+
+    /// the route for `part` (1 or 2) drawn over the heat loss grid, or with `format=coords`
+    /// just the blocks it goes through as `x,y` lines
+    fn render(&self) -> Option<String> {
+        let map = self.parse_input();
+        let part = self.params.get("part", 1);
+        let (min_run, max_run) = match part {
+            1 => (
+                self.params.get("min_run1", 1),
+                self.params.get("max_run1", 3),
+            ),
+            _ => (
+                self.params.get("min_run2", 4),
+                self.params.get("max_run2", 10),
+            ),
+        };
+        let Some(route) = map.find_route(min_run, max_run) else {
+            return Some("no route".to_string());
+        };
+        match self.params.get("format", "grid".to_string()).as_str() {
+            "coords" => Some(
+                route
+                    .path
+                    .iter()
+                    .map(|(x, y)| format!("{x},{y}\n"))
+                    .collect(),
+            ),
+            _ => Some(format!(
+                "heat loss: {}, runs: {:?}\n{}",
+                route.heat_loss,
+                route.runs(),
+                map.render_route(&route)
+            )),
+        }
+    }
+}
+
+/// which way the crucible was moving when it got to a block, it has to turn onto the other one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    fn turn(self) -> Self {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Pos {
-    x: usize,
-    y: usize,
+/// the cheapest way from the top left to the bottom right, and every block it goes through
+#[derive(Debug, Clone)]
+struct Route {
     heat_loss: usize,
+    path: Vec<(usize, usize)>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
-struct Direction {
-    pos: Pos,
-    delta: (i8, i8),
-    count: u8,
+impl Route {
+    /// how many blocks each straight line is before the crucible turns
+    fn runs(&self) -> Vec<usize> {
+        let mut runs: Vec<usize> = Vec::new();
+        let mut last_delta = None;
+        for step in self.path.windows(2) {
+            let delta = Route::delta(step[0], step[1]);
+            match runs.last_mut() {
+                Some(run) if last_delta == Some(delta) => *run += 1,
+                _ => runs.push(1),
+            }
+            last_delta = Some(delta);
+        }
+        runs
+    }
+
+    fn delta(from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+        (
+            to.0 as isize - from.0 as isize,
+            to.1 as isize - from.1 as isize,
+        )
+    }
+}
+
+struct HeatMap {
+    width: usize,
+    height: usize,
+    heat_loss: Vec<usize>,
+}
+
+impl HeatMap {
+    /// states are (block, axis it arrived on) packed into one index
+    fn state(&self, (x, y): (usize, usize), axis: Axis) -> usize {
+        (y * self.width + x) * 2 + axis as usize
+    }
+
+    fn unpack(&self, state: usize) -> ((usize, usize), Axis) {
+        let block = state / 2;
+        let axis = match state % 2 {
+            0 => Axis::Horizontal,
+            _ => Axis::Vertical,
+        };
+        ((block % self.width, block / self.width), axis)
+    }
+
+    /// dijkstra over (x, y, axis) where every move is a turn followed by `min_run..=max_run`
+    /// blocks in a straight line, so the run limits never have to be tracked in the state.
+    /// Heat loss per block is at most 9, so the queue is a bucket per total heat loss.
+    fn find_route(&self, min_run: usize, max_run: usize) -> Option<Route> {
+        let goal = (self.width - 1, self.height - 1);
+        let mut best = vec![usize::MAX; self.width * self.height * 2];
+        let mut previous = vec![None; best.len()];
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new()];
+        // the first move can go either way
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            let start = self.state((0, 0), axis);
+            best[start] = 0;
+            buckets[0].push(start);
+        }
+
+        let mut heat_loss = 0;
+        while heat_loss < buckets.len() {
+            while let Some(state) = buckets[heat_loss].pop() {
+                if best[state] < heat_loss {
+                    // already got here cheaper
+                    continue;
+                }
+                let ((x, y), axis) = self.unpack(state);
+                if (x, y) == goal {
+                    return Some(self.build_route(state, heat_loss, &previous));
+                }
+                let next_axis = axis.turn();
+                let deltas: [(isize, isize); 2] = match next_axis {
+                    Axis::Horizontal => [(1, 0), (-1, 0)],
+                    Axis::Vertical => [(0, 1), (0, -1)],
+                };
+                for (dx, dy) in deltas {
+                    let mut next_heat_loss = heat_loss;
+                    for run in 1..=max_run {
+                        let next_x = x as isize + dx * run as isize;
+                        let next_y = y as isize + dy * run as isize;
+                        if next_x < 0
+                            || next_y < 0
+                            || next_x >= self.width as isize
+                            || next_y >= self.height as isize
+                        {
+                            break;
+                        }
+                        let next_block = (next_x as usize, next_y as usize);
+                        next_heat_loss += self.heat_loss[next_block.1 * self.width + next_block.0];
+                        if run < min_run {
+                            continue;
+                        }
+                        let next_state = self.state(next_block, next_axis);
+                        if next_heat_loss < best[next_state] {
+                            best[next_state] = next_heat_loss;
+                            previous[next_state] = Some(state);
+                            if next_heat_loss >= buckets.len() {
+                                buckets.resize(next_heat_loss + 1, Vec::new());
+                            }
+                            buckets[next_heat_loss].push(next_state);
+                        }
+                    }
+                }
+            }
+            heat_loss += 1;
+        }
+        None
+    }
+
+    /// the heat loss grid with an arrow on each block of the route showing which way it went in
+    fn render_route(&self, route: &Route) -> String {
+        let mut grid = self
+            .heat_loss
+            .iter()
+            .map(|heat_loss| char::from_digit(*heat_loss as u32, 10).unwrap())
+            .collect::<Vec<char>>();
+        for step in route.path.windows(2) {
+            let (x, y) = step[1];
+            grid[y * self.width + x] = match Route::delta(step[0], step[1]) {
+                (1, 0) => '>',
+                (-1, 0) => '<',
+                (0, 1) => 'v',
+                _ => '^',
+            };
+        }
+        grid.chunks(self.width)
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    /// walks the turns back to the start, filling in the blocks along each straight run
+    fn build_route(&self, end: usize, heat_loss: usize, previous: &[Option<usize>]) -> Route {
+        let mut path = vec![self.unpack(end).0];
+        let mut state = end;
+        while let Some(previous_state) = previous[state] {
+            let (from, _) = self.unpack(previous_state);
+            let (mut x, mut y) = self.unpack(state).0;
+            while (x, y) != from {
+                x = if x > from.0 {
+                    x - 1
+                } else if x < from.0 {
+                    x + 1
+                } else {
+                    x
+                };
+                y = if y > from.1 {
+                    y - 1
+                } else if y < from.1 {
+                    y + 1
+                } else {
+                    y
+                };
+                path.push((x, y));
+            }
+            state = previous_state;
+        }
+        path.reverse();
+        Route { heat_loss, path }
+    }
 }
@@ -1,6 +1,7 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use super::{get_lines, Day};
+use crate::utils::polygon::{Polygon, Vertex};
 
 pub struct Day18 {
     input: String,
@@ -18,65 +19,62 @@ impl Day18 {
             .collect()
     }
 
-    /// returns a bunch of 2x2 arrays for us to do the shoelace formula on
-    /// returns as (x1, y1), (x2, y2)
-    fn get_matrices(steps: Vec<Step>) -> Vec<((isize, isize), (isize, isize))> {
-        let mut map = vec![];
-        let mut matricies = vec![];
-        let mut x = 0;
-        let mut y = 0;
-        let mut min_x = 0;
-        let mut min_y = 0;
-        for step in steps {
-            let start_x = x;
-            let start_y = y;
-            match step.direction {
-                Direction::Up => y -= step.length,
-                Direction::Down => y += step.length,
-                Direction::Right => x += step.length,
-                Direction::Left => x -= step.length,
-            }
-            map.push(Trench {
-                start_x,
-                start_y,
-                end_x: x,
-                end_y: y,
-            });
-            if x < min_x {
-                min_x = x;
-            }
-            if y < min_y {
-                min_y = y;
-            }
+    /// the trench as a polygon through the middle of each dug block, starting at the origin,
+    /// checking that it's a simple loop
+    fn get_polygon(steps: &[Step]) -> Result<Polygon, PlanError> {
+        let moves = steps.iter().map(Step::delta).collect::<Vec<Vertex>>();
+        let end = moves
+            .iter()
+            .fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy));
+        if end != (0, 0) {
+            return Err(PlanError::NotClosed(end));
+        }
+        let polygon = Polygon::from_moves((0, 0), moves);
+        if let Some((first, second)) = polygon.self_intersection() {
+            return Err(PlanError::SelfIntersects(first, second));
+        }
+        Ok(polygon)
+    }
+
+    /// the trench itself plus everything inside it
+    fn get_lagoon_size(steps: &[Step]) -> Result<i64, PlanError> {
+        let polygon = Self::get_polygon(steps)?;
+        Ok(polygon.interior_points() + polygon.boundary_points())
+    }
+
+    /// the trench as a plain text PPM image, each block coloured by the step that dug it
+    fn render_trench(steps: &[Step]) -> Result<String, PlanError> {
+        let polygon = Self::get_polygon(steps)?;
+        let Some(((min_x, min_y), (max_x, max_y))) = polygon.bounding_box() else {
+            return Ok("P3\n0 0\n255\n".to_string());
+        };
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        if width * height > MAX_RENDER_PIXELS {
+            return Err(PlanError::TooLarge(width, height));
         }
-        // normalize the map
-        for trench in &mut map {
-            trench.start_x -= min_x;
-            trench.end_x -= min_x;
-            trench.start_y -= min_y;
-            trench.end_y -= min_y;
-            let insert = (
-                (trench.start_x, trench.start_y),
-                (trench.end_x, trench.end_y),
-            );
-            if !matricies.contains(&insert) {
-                matricies.push(insert);
+        // undug ground is white
+        let mut pixels = vec![(255, 255, 255); width * height];
+        let (mut x, mut y) = (-min_x, -min_y);
+        for step in steps {
+            let color = step.rgb();
+            let (dx, dy) = step.delta();
+            for _ in 0..step.length {
+                x += dx.signum();
+                y += dy.signum();
+                pixels[y as usize * width + x as usize] = color;
             }
         }
-        matricies
-    }
-
-    // coming in as (x1,y1,x2,y2)
-    fn shoelace_formula(matricies: Vec<((isize, isize), (isize, isize))>) -> isize {
-        let mut sum = 0;
-        for matrix in matricies {
-            let (x1, y1) = matrix.0;
-            let (x2, y2) = matrix.1;
-            sum += (y1 + y2) * (x1 - x2);
-            sum += x1.abs_diff(x2) as isize;
-            sum += y1.abs_diff(y2) as isize;
+        let mut ppm = format!("P3\n{width} {height}\n255\n");
+        for row in pixels.chunks(width) {
+            let row = row
+                .iter()
+                .map(|(r, g, b)| format!("{r} {g} {b}"))
+                .collect::<Vec<String>>();
+            ppm.push_str(&row.join(" "));
+            ppm.push('\n');
         }
-        sum / 2 + 1
+        Ok(ppm)
     }
 
     fn convert_steps(steps: Vec<Step>) -> Vec<Step> {
@@ -106,29 +104,59 @@ impl Day for Day18 {
     fn part1(&self) -> String {
         let start = std::time::Instant::now();
         let steps = self.parse_input();
-        let mats = Self::get_matrices(steps);
-        let inner = Self::shoelace_formula(mats);
+        let inner = Self::get_lagoon_size(&steps);
         println!("Time: {:?}", start.elapsed());
-        format!("{inner}")
+        match inner {
+            Ok(inner) => format!("{inner}"),
+            Err(err) => format!("{err}"),
+        }
     }
 
     fn part2(&self) -> String {
         let start = std::time::Instant::now();
         let steps = self.parse_input();
         let steps = Self::convert_steps(steps);
-        let mats = Self::get_matrices(steps);
-        let inner = Self::shoelace_formula(mats);
+        let inner = Self::get_lagoon_size(&steps);
         println!("Time: {:?}", start.elapsed());
-        format!("{inner}")
+        match inner {
+            Ok(inner) => format!("{inner}"),
+            Err(err) => format!("{err}"),
+        }
+    }
+
+    /// part 1's dig plan as a PPM image, write it out with `--output`
+    fn render(&self) -> Option<String> {
+        let steps = self.parse_input();
+        Some(Self::render_trench(&steps).unwrap_or_else(|err| format!("{err}")))
     }
 }
 
-#[derive(Clone, Debug)]
-struct Trench {
-    start_x: isize,
-    start_y: isize,
-    end_x: isize,
-    end_y: isize,
+/// part 2 sized plans would be billions of pixels
+const MAX_RENDER_PIXELS: usize = 10_000_000;
+
+#[derive(Debug)]
+enum PlanError {
+    /// where the last step ends up
+    NotClosed(Vertex),
+    /// indices of the two steps that cross
+    SelfIntersects(usize, usize),
+    TooLarge(usize, usize),
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::NotClosed(end) => {
+                write!(f, "dig plan ends at {end:?} instead of back at the start")
+            }
+            PlanError::SelfIntersects(first, second) => {
+                write!(f, "dig plan crosses itself at steps {first} and {second}")
+            }
+            PlanError::TooLarge(width, height) => {
+                write!(f, "dig plan is {width}x{height}, too big to render")
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -159,6 +187,24 @@ struct Step {
     color: String,
 }
 
+impl Step {
+    fn delta(&self) -> Vertex {
+        let length = self.length as i64;
+        match self.direction {
+            Direction::Up => (0, -length),
+            Direction::Down => (0, length),
+            Direction::Right => (length, 0),
+            Direction::Left => (-length, 0),
+        }
+    }
+
+    /// the `#rrggbb` color as numbers
+    fn rgb(&self) -> (u8, u8, u8) {
+        let channel = |idx: usize| u8::from_str_radix(&self.color[idx..idx + 2], 16).unwrap();
+        (channel(1), channel(3), channel(5))
+    }
+}
+
 impl FromStr for Step {
     type Err = ();
 
@@ -181,3 +227,42 @@ impl FromStr for Step {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn sample() {
+        let steps = Day18::new(SAMPLE.to_string()).parse_input();
+        assert!(Day18::get_polygon(&steps).is_ok());
+        assert_eq!(Day18::get_lagoon_size(&steps).unwrap(), 62);
+        let steps = Day18::convert_steps(steps);
+        assert!(Day18::get_polygon(&steps).is_ok());
+        assert_eq!(Day18::get_lagoon_size(&steps).unwrap(), 952408144115);
+    }
+
+    #[test]
+    fn zero_length_step() {
+        let steps = Day18::new(
+            "R 3 (#000000)\nR 0 (#000000)\nD 2 (#000000)\nL 3 (#000000)\nU 2 (#000000)".to_string(),
+        )
+        .parse_input();
+        assert_eq!(Day18::get_lagoon_size(&steps).unwrap(), 12);
+    }
+}
@@ -1,26 +1,30 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
     ops::Add,
     str::FromStr,
-    sync::{Arc, Mutex},
 };
 
-use super::Day;
+use super::{Day, Params};
+use crate::utils::parallel::sum_chunks;
 
 pub struct Day19 {
     input: String,
+    params: Params,
 }
 
 impl Day19 {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["trace", "verify"];
+
+    pub fn new(input: String, params: Params) -> Self {
+        Self { input, params }
     }
 
     fn parse_input(&self) -> (Vec<Part>, HashMap<String, Workflow>) {
         let mut parts = Vec::new();
         let mut workflows = HashMap::new();
-        let mut split = self.input.lines();
-        while let Some(line) = split.next() {
+        for line in self.input.lines() {
             if line.is_empty() {
                 continue;
             }
@@ -42,130 +46,204 @@ impl Day for Day19 {
     fn part1(&self) -> String {
         let start_time = std::time::Instant::now();
         let (parts, workflows) = self.parse_input();
+        let graph = match Self::compile(&workflows) {
+            Ok(graph) => graph,
+            Err(err) => return format!("{err}"),
+        };
+        // with `trace=true` every part's route through the workflows is printed
+        let trace = self.params.get("trace", false);
+        let verify = self.params.get("verify", DEFAULT_VERIFY) > 0;
         let mut accepts = Vec::new();
         let mut accept_sum = 0;
         for part in parts {
-            let mut action = workflows.get("in").unwrap().apply(&part);
-
-            loop {
-                match action {
-                    Action::Accept => {
-                        accept_sum += part.sum();
-                        accepts.push(part);
-                        break;
-                    }
-                    Action::Reject => break,
-                    Action::Workflow(key) => {
-                        action = workflows.get(&key).unwrap().apply(&part);
-                    }
-                }
+            if trace {
+                println!("{part}: {}", Self::trace(&workflows, &part));
+            }
+            let accepted = graph.accepts(part.ratings());
+            if verify {
+                assert_eq!(
+                    accepted,
+                    Self::is_accepted(&workflows, &part),
+                    "decision graph and workflows disagree on {part}"
+                );
+            }
+            if accepted {
+                accept_sum += part.sum();
+                accepts.push(part);
             }
         }
         println!("part 1 took: {:?}", start_time.elapsed());
         format!("{accept_sum}")
     }
     fn part2(&self) -> String {
-        // let start_time = std::time::Instant::now();
-        // let (parts, workflows) = self.parse_input();
-        // let workflow_arc = Arc::new(workflows);
-        // let num_runners = 32;
-        // let threadpool = ThreadPool::new(num_runners);
-        // let (tx, rx) = std::sync::mpsc::channel();
-        // let bound_starts = vec![
-        //     1, 126, 251, 376, 501, 626, 751, 876, 1001, 1126, 1251, 1376, 1501, 1626, 1751, 1876,
-        //     2001, 2126, 2251, 2376, 2501, 2626, 2751, 2876, 3001, 3126, 3251, 3376, 3501, 3626,
-        //     3751, 3876,
-        // ];
-        // let bound_ends = vec![
-        //     125, 250, 375, 500, 625, 750, 875, 1000, 1125, 1250, 1375, 1500, 1625, 1750, 1875,
-        //     2000, 2125, 2250, 2375, 2500, 2625, 2750, 2875, 3000, 3125, 3250, 3375, 3500, 3625,
-        //     3750, 3875, 4000,
-        // ];
-        // for (start, end) in bound_starts.iter().zip(bound_ends.iter()) {
-        //     let workflows = workflow_arc.clone();
-        //     let tx_clone = tx.clone();
-        //     let start_clone = start.clone();
-        //     let end_clone = end.clone();
-        //     threadpool.execute(move || {
-        //         do_workflows(workflows, start_clone, end_clone, tx_clone);
-        //     });
-        // }
-        // println!("waiting for threads");
-        // let sum = rx.iter().take(num_runners).fold(0, |acc, x| acc + x);
-        // println!("part 1 took: {:?}", start_time.elapsed());
-        // format!("{sum}")
         let (_, workflows) = self.parse_input();
-        let range = RangeGroup {
-            x: (1, 4000),
-            m: (1, 4000),
-            a: (1, 4000),
-            s: (1, 4000),
+        let graph = match Self::compile(&workflows) {
+            Ok(graph) => graph,
+            Err(err) => return format!("{err}"),
         };
-        let mut accept_sum = 0;
-        let actions = workflows.get("in").unwrap().apply_to_range(&range);
-        let mut actions = VecDeque::from(actions);
+        let range = RangeGroup::new([RATING_RANGE; 4]);
+        let accept_sum = graph.count_accepted(range.clone());
+        // with `verify=N` the answer is checked against interpreting the workflows directly, and
+        // against brute force over N values of each rating
+        let verify = self.params.get("verify", DEFAULT_VERIFY);
+        if verify > 0 {
+            assert_eq!(
+                accept_sum,
+                Self::count_accepted(&workflows, range),
+                "decision graph and workflows disagree on the full range"
+            );
+            Self::verify(&workflows, &graph, verify);
+        }
+        format!("{accept_sum}")
+    }
 
-        while let Some((action, range)) = actions.pop_front() {
+    /// the compiled decision graph as graphviz DOT
+    fn render(&self) -> Option<String> {
+        let (_, workflows) = self.parse_input();
+        Some(match Self::compile(&workflows) {
+            Ok(graph) => graph.to_dot(),
+            Err(err) => format!("{err}"),
+        })
+    }
+}
+
+impl Day19 {
+    /// compiles the workflows, printing anything that got simplified away to stderr so renders
+    /// stay valid DOT
+    fn compile(workflows: &HashMap<String, Workflow>) -> Result<DecisionGraph, WorkflowError> {
+        let graph = DecisionGraph::compile(workflows)?;
+        for (key, idx) in &graph.unreachable_rules {
+            eprintln!("rule {idx} of {key} can never fire");
+        }
+        for key in &graph.unreachable_workflows {
+            eprintln!("workflow {key} is never reached from in");
+        }
+        Ok(graph)
+    }
+
+    /// follows `part` from the `in` workflow until it's accepted or rejected
+    fn is_accepted(workflows: &HashMap<String, Workflow>, part: &Part) -> bool {
+        let (_, mut action) = workflows.get("in").unwrap().apply(part);
+        loop {
+            match action {
+                Action::Accept => return true,
+                Action::Reject => return false,
+                Action::Workflow(key) => {
+                    action = workflows.get(key).unwrap().apply(part).1;
+                }
+            }
+        }
+    }
+
+    /// each workflow `part` goes through with the rule that fired in it, then where it ended up
+    fn trace(workflows: &HashMap<String, Workflow>, part: &Part) -> String {
+        let mut route = Vec::new();
+        let mut key = "in";
+        loop {
+            let workflow = workflows.get(key).unwrap();
+            let (idx, action) = workflow.apply(part);
+            route.push(format!("{key}[{}]", workflow.filters[idx]));
             match action {
-                Action::Accept => {
-                    // let count = range.count();
-                    // dbg!(accept_sum, count);
-                    dbg!(&range);
-                    accept_sum += range.count();
+                Action::Workflow(next) => key = next.as_str(),
+                Action::Accept | Action::Reject => {
+                    route.push(action.to_string());
+                    return route.join(" -> ");
                 }
+            }
+        }
+    }
+
+    /// how many parts with ratings inside `range` end up accepted
+    fn count_accepted(workflows: &HashMap<String, Workflow>, range: RangeGroup) -> usize {
+        let mut accept_sum = 0;
+        let mut actions = VecDeque::from([(Action::Workflow("in".to_string()), range)]);
+        while let Some((action, range)) = actions.pop_front() {
+            match action {
+                Action::Accept => accept_sum += range.count(),
                 Action::Reject => {}
                 Action::Workflow(key) => {
-                    let mut next_actions = workflows.get(&key).unwrap().apply_to_range(&range);
-                    for next_action in next_actions {
-                        actions.push_back(next_action);
-                    }
-                    // panic!()
+                    actions.extend(workflows.get(&key).unwrap().apply_to_range(&range));
                 }
             }
         }
-        format!("{accept_sum}")
+        accept_sum
     }
-}
 
-fn do_workflows(
-    workflows: Arc<HashMap<String, Workflow>>,
-    range_start: usize,
-    range_end: usize,
-    tx: std::sync::mpsc::Sender<usize>,
-) {
-    let mut sum = 0;
-    for x in range_start..=range_end {
-        for m in 1..=4000 {
-            let x_start_time = std::time::Instant::now();
-            for a in 1..=4000 {
-                for s in 1..=4000 {
-                    let part = Part {
-                        x: Criteria::X(x),
-                        m: Criteria::M(m),
-                        a: Criteria::A(a),
-                        s: Criteria::S(s),
-                    };
-                    let mut action = workflows.get("in").unwrap().apply(&part);
-                    loop {
-                        match action {
-                            Action::Accept => {
-                                sum += 1;
-                                break;
-                            }
-                            Action::Reject => break,
-                            Action::Workflow(key) => {
-                                action = workflows.get(&key).unwrap().apply(&part);
-                            }
-                        }
-                    }
+    /// brute forces ratings 1..=`size`, then `size` ratings around each rating's middle cutoff
+    /// so the interesting splits get covered too
+    fn verify(workflows: &HashMap<String, Workflow>, graph: &DecisionGraph, size: usize) {
+        let size = size.min(RATING_RANGE.1);
+        let low = RangeGroup::new([(RATING_RANGE.0, size); 4]);
+        let window = RangeGroup::new(std::array::from_fn(|dim| {
+            let mut cutoffs = workflows
+                .values()
+                .flat_map(|workflow| &workflow.filters)
+                .filter_map(Filter::cutoff)
+                .filter(|cutoff| cutoff.dimension() == dim)
+                .map(|cutoff| cutoff.value())
+                .collect::<Vec<usize>>();
+            cutoffs.sort();
+            let middle = cutoffs.get(cutoffs.len() / 2).copied().unwrap_or(2000);
+            let start = middle
+                .saturating_sub(size / 2)
+                .clamp(RATING_RANGE.0, RATING_RANGE.1 + 1 - size);
+            (start, start + size - 1)
+        }));
+        for domain in [low, window] {
+            match Self::brute_force_count(workflows, &domain) {
+                Some(count) => {
+                    assert_eq!(
+                        graph.count_accepted(domain.clone()),
+                        count,
+                        "decision graph and brute force disagree over {:?}",
+                        domain.ranges
+                    );
+                    assert_eq!(
+                        Self::count_accepted(workflows, domain.clone()),
+                        count,
+                        "workflow range splitting and brute force disagree over {:?}",
+                        domain.ranges
+                    );
+                    println!(
+                        "brute force agrees over {:?}: {count} accepted",
+                        domain.ranges
+                    );
                 }
+                None => println!("{:?} is too big to brute force", domain.ranges),
             }
-            println!("x took: {:?}", x_start_time.elapsed(),);
         }
     }
-    tx.send(sum).unwrap();
+
+    /// checks every part inside `range` one at a time by interpreting the workflows, the same
+    /// way part 1 is verified, splitting the `x` ratings across threads. `None` if that's more
+    /// than `MAX_BRUTE_FORCE` parts
+    fn brute_force_count(
+        workflows: &HashMap<String, Workflow>,
+        range: &RangeGroup,
+    ) -> Option<usize> {
+        if range.count() > MAX_BRUTE_FORCE {
+            return None;
+        }
+        let (start, end) = range.ranges[0];
+        let xs = (start..=end).collect::<Vec<usize>>();
+        let count = sum_chunks(&xs, |xs| {
+            let mut chunk = range.clone();
+            chunk.ranges[0] = (xs[0], xs[xs.len() - 1]);
+            chunk
+                .points()
+                .filter(|ratings| Self::is_accepted(workflows, &Part::from_ratings(*ratings)))
+                .count()
+        });
+        Some(count)
+    }
 }
 
+/// ratings per axis to verify against brute force with, only on by default in debug builds
+const DEFAULT_VERIFY: usize = if cfg!(debug_assertions) { 10 } else { 0 };
+
+/// the most parts `Day19::brute_force_count` will check, 1..=100 for every rating
+const MAX_BRUTE_FORCE: usize = 100 * 100 * 100 * 100;
+
 #[derive(Debug, Clone)]
 enum Action {
     Accept,
@@ -173,6 +251,16 @@ enum Action {
     Workflow(String),
 }
 
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Accept => write!(f, "A"),
+            Action::Reject => write!(f, "R"),
+            Action::Workflow(key) => write!(f, "{key}"),
+        }
+    }
+}
+
 impl FromStr for Action {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -198,330 +286,44 @@ impl Filter {
         check: RangeGroup,
     ) -> (Option<(Action, RangeGroup)>, Option<RangeGroup>) {
         match self {
-            Filter::GreaterThan { cutoff, action } => match cutoff {
-                Criteria::X(v) => {
-                    let (x_range_start, x_range_end) = check.x;
-                    if x_range_start > *v {
-                        // whole range is above cutoff
-                        (Some((action.clone(), check)), None)
-                    } else if x_range_end < *v {
-                        // whole range is below cutoff
-                        (None, Some(check))
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: (*v + 1, x_range_end),
-                                    m: check.m,
-                                    a: check.a,
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: (x_range_start, *v),
-                                m: check.m,
-                                a: check.a,
-                                s: check.a,
-                            }),
-                        )
-                    }
-                }
-                Criteria::M(v) => {
-                    let (m_range_start, m_range_end) = check.m;
-                    if m_range_start > *v {
-                        // whole range is above cutoff
-                        (Some((action.clone(), check)), None)
-                    } else if m_range_end < *v {
-                        // whole range is below cutoff
-                        (None, Some(check))
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    m: (*v + 1, m_range_end),
-                                    x: check.x,
-                                    a: check.a,
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: (m_range_start, *v),
-                                a: check.a,
-                                s: check.s,
-                            }),
-                        )
-                    }
-                }
-                Criteria::A(v) => {
-                    let (a_range_start, a_range_end) = check.a;
-                    if a_range_start > *v {
-                        // whole range above cutoff
-                        (Some((action.clone(), check)), None)
-                    } else if a_range_end < *v {
-                        // whole range below cutoff
-                        (None, Some(check))
-                    } else {
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: check.x,
-                                    m: check.m,
-                                    a: (*v + 1, a_range_end),
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: check.m,
-                                a: (a_range_start, *v),
-                                s: check.s,
-                            }),
-                        )
-                    }
-                }
-                Criteria::S(v) => {
-                    let (s_range_start, s_range_end) = check.s;
-                    if s_range_start > *v {
-                        (Some((action.clone(), check)), None)
-                    } else if s_range_end < *v {
-                        (None, Some(check))
-                    } else {
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: check.x,
-                                    m: check.m,
-                                    a: check.a,
-                                    s: (*v + 1, s_range_end),
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: check.m,
-                                a: check.a,
-                                s: (s_range_start, *v),
-                            }),
-                        )
-                    }
-                }
-            },
-            Filter::LessThan { cutoff, action } => match cutoff {
-                Criteria::X(v) => {
-                    let (x_range_start, x_range_end) = check.x;
-                    if x_range_start > *v {
-                        // whole range is above cutoff
-                        (None, Some(check))
-                    } else if x_range_end < *v {
-                        // whole range is below cutoff
-                        (Some((action.clone(), check)), None)
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: (x_range_start, *v - 1),
-                                    m: check.m,
-                                    a: check.a,
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: (*v, x_range_end),
-                                m: check.m,
-                                a: check.a,
-                                s: check.s,
-                            }),
-                        )
-                    }
-                }
-                Criteria::M(v) => {
-                    let (m_range_start, m_range_end) = check.m;
-                    if m_range_start > *v {
-                        // whole range is above cutoff
-                        (None, Some(check))
-                    } else if m_range_end < *v {
-                        // whole range is below cutoff
-                        (Some((action.clone(), check)), None)
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: check.x,
-                                    m: (m_range_start, *v - 1),
-                                    a: check.a,
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: (*v, m_range_end),
-                                a: check.a,
-                                s: check.s,
-                            }),
-                        )
-                    }
-                }
-                Criteria::A(v) => {
-                    let (a_range_start, a_range_end) = check.a;
-                    if a_range_start > *v {
-                        // whole range is above cutoff
-                        (None, Some(check))
-                    } else if a_range_end < *v {
-                        // whole range is below cutoff
-                        (Some((action.clone(), check)), None)
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: check.x,
-                                    m: check.m,
-                                    a: (a_range_start, *v - 1),
-                                    s: check.s,
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: check.m,
-                                a: (*v, a_range_end),
-                                s: check.s,
-                            }),
-                        )
-                    }
-                }
-                Criteria::S(v) => {
-                    let (s_range_start, s_range_end) = check.s;
-                    if s_range_start > *v {
-                        // whole range is above cutoff
-                        (None, Some(check))
-                    } else if s_range_end < *v {
-                        // whole range is below cutoff
-                        (Some((action.clone(), check)), None)
-                    } else {
-                        // new range time
-                        (
-                            Some((
-                                action.clone(),
-                                RangeGroup {
-                                    x: check.x,
-                                    m: check.m,
-                                    a: check.a,
-                                    s: (s_range_start, *v - 1),
-                                },
-                            )),
-                            Some(RangeGroup {
-                                x: check.x,
-                                m: check.m,
-                                a: check.a,
-                                s: (*v, s_range_end),
-                            }),
-                        )
-                    }
-                }
-                _ => todo!(),
-            },
+            Filter::GreaterThan { cutoff, action } => {
+                let (rest, matched) = check.split(cutoff.dimension(), cutoff.value() + 1);
+                (matched.map(|matched| (action.clone(), matched)), rest)
+            }
+            Filter::LessThan { cutoff, action } => {
+                let (matched, rest) = check.split(cutoff.dimension(), cutoff.value());
+                (matched.map(|matched| (action.clone(), matched)), rest)
+            }
             Filter::Bare(a) => (Some((a.clone(), check)), None),
         }
     }
 
-    fn apply(&self, check: Criteria) -> Option<Action> {
+    fn cutoff(&self) -> Option<Criteria> {
         match self {
-            Filter::GreaterThan { cutoff, action } => match cutoff {
-                Criteria::X(v) => match check {
-                    Criteria::X(c) => {
-                        if c > *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::M(v) => match check {
-                    Criteria::M(c) => {
-                        if c > *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::A(v) => match check {
-                    Criteria::A(c) => {
-                        if c > *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::S(v) => match check {
-                    Criteria::S(c) => {
-                        if c > *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-            },
-            Filter::LessThan { cutoff, action } => match cutoff {
-                Criteria::X(v) => match check {
-                    Criteria::X(c) => {
-                        if c < *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::M(v) => match check {
-                    Criteria::M(c) => {
-                        if c < *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::A(v) => match check {
-                    Criteria::A(c) => {
-                        if c < *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-                Criteria::S(v) => match check {
-                    Criteria::S(c) => {
-                        if c < *v {
-                            Some(action.clone())
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                },
-            },
-            Filter::Bare(action) => Some(action.clone()),
+            Filter::GreaterThan { cutoff, .. } | Filter::LessThan { cutoff, .. } => Some(*cutoff),
+            Filter::Bare(_) => None,
+        }
+    }
+
+    fn apply(&self, check: Criteria) -> Option<&Action> {
+        match self {
+            Filter::GreaterThan { cutoff, action } => (cutoff.dimension() == check.dimension()
+                && check.value() > cutoff.value())
+            .then_some(action),
+            Filter::LessThan { cutoff, action } => (cutoff.dimension() == check.dimension()
+                && check.value() < cutoff.value())
+            .then_some(action),
+            Filter::Bare(action) => Some(action),
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::GreaterThan { cutoff, .. } => write!(f, "{}>{}", cutoff.name(), cutoff.value()),
+            Filter::LessThan { cutoff, .. } => write!(f, "{}<{}", cutoff.name(), cutoff.value()),
+            Filter::Bare(_) => write!(f, "fallback"),
         }
     }
 }
@@ -574,25 +376,52 @@ impl Workflow {
         range_actions
     }
 
-    fn apply(&self, part: &Part) -> Action {
-        for filter in &self.filters {
-            if let Some(action) = filter.apply(part.x.clone()) {
-                return action;
-            }
-            if let Some(action) = filter.apply(part.m.clone()) {
-                return action;
-            }
-            if let Some(action) = filter.apply(part.a.clone()) {
-                return action;
-            }
-            if let Some(action) = filter.apply(part.s.clone()) {
-                return action;
+    /// the index of the first rule `part` matches, and what that rule does with it
+    fn apply<'a>(&'a self, part: &Part) -> (usize, &'a Action) {
+        for (idx, filter) in self.filters.iter().enumerate() {
+            for rating in [part.x, part.m, part.a, part.s] {
+                if let Some(action) = filter.apply(rating) {
+                    return (idx, action);
+                }
             }
         }
-        panic!("no action found")
+        unreachable!("workflows are checked for a fallback rule when compiled")
     }
 }
 
+impl Workflow {
+    /// for each rule, whether any part that gets to it can match it
+    fn coverage(&self) -> Vec<Coverage> {
+        let mut remaining = vec![RangeGroup::new([RATING_RANGE; 4])];
+        self.filters
+            .iter()
+            .map(|filter| {
+                let mut matched = false;
+                let mut next_remaining = Vec::new();
+                for range in remaining.drain(..) {
+                    let (action, rest) = filter.apply_to_range(range);
+                    matched |= action.is_some();
+                    next_remaining.extend(rest);
+                }
+                remaining = next_remaining;
+                match (matched, remaining.is_empty()) {
+                    (false, _) => Coverage::Never,
+                    (true, false) => Coverage::Sometimes,
+                    (true, true) => Coverage::Always,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coverage {
+    Never,
+    Sometimes,
+    /// nothing falls through to the rules after it
+    Always,
+}
+
 impl FromStr for Workflow {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -622,11 +451,36 @@ struct Part {
 }
 
 impl Part {
+    fn from_ratings([x, m, a, s]: [usize; 4]) -> Self {
+        Part {
+            x: Criteria::X(x),
+            m: Criteria::M(m),
+            a: Criteria::A(a),
+            s: Criteria::S(s),
+        }
+    }
+
+    fn ratings(&self) -> [usize; 4] {
+        [
+            self.x.value(),
+            self.m.value(),
+            self.a.value(),
+            self.s.value(),
+        ]
+    }
+
     fn sum(&self) -> usize {
         (self.x + self.m) + (self.a + self.s)
     }
 }
 
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [x, m, a, s] = self.ratings();
+        write!(f, "{{x={x},m={m},a={a},s={s}}}")
+    }
+}
+
 impl FromStr for Part {
     type Err = ();
 
@@ -650,6 +504,28 @@ enum Criteria {
     S(usize),
 }
 
+impl Criteria {
+    /// which axis of a `RangeGroup` this rating is
+    fn dimension(&self) -> usize {
+        match self {
+            Criteria::X(_) => 0,
+            Criteria::M(_) => 1,
+            Criteria::A(_) => 2,
+            Criteria::S(_) => 3,
+        }
+    }
+
+    fn name(&self) -> char {
+        ['x', 'm', 'a', 's'][self.dimension()]
+    }
+
+    fn value(&self) -> usize {
+        match self {
+            Criteria::X(v) | Criteria::M(v) | Criteria::A(v) | Criteria::S(v) => *v,
+        }
+    }
+}
+
 impl Add for Criteria {
     type Output = usize;
 
@@ -692,21 +568,476 @@ impl FromStr for Criteria {
     }
 }
 
-#[derive(Debug, Clone)]
-struct RangeGroup {
-    x: (usize, usize),
-    m: (usize, usize),
-    a: (usize, usize),
-    s: (usize, usize),
+/// every rating is somewhere in here
+const RATING_RANGE: (usize, usize) = (1, 4000);
+
+/// the graph nodes every workflow ends up at
+const ACCEPT: usize = 0;
+const REJECT: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Accept,
+    Reject,
+    /// ratings along `dimension` below `at` go to `below`, the rest go to `above`
+    Branch {
+        dimension: usize,
+        at: usize,
+        below: usize,
+        above: usize,
+    },
 }
 
-impl RangeGroup {
+/// all the workflows reachable from `in` compiled down to one binary decision DAG
+#[derive(Debug)]
+struct DecisionGraph {
+    nodes: Vec<Node>,
+    /// node -> index, so identical branches are only built once
+    interned: HashMap<Node, usize>,
+    start: usize,
+    /// `(workflow, rule index)` for rules no part could ever match
+    unreachable_rules: Vec<(String, usize)>,
+    unreachable_workflows: Vec<String>,
+}
+
+impl DecisionGraph {
+    fn compile(workflows: &HashMap<String, Workflow>) -> Result<Self, WorkflowError> {
+        let mut keys = workflows.keys().collect::<Vec<&String>>();
+        keys.sort();
+        let mut unreachable_rules = Vec::new();
+        for key in &keys {
+            let workflow = &workflows[*key];
+            for filter in &workflow.filters {
+                let (Filter::GreaterThan { action, .. }
+                | Filter::LessThan { action, .. }
+                | Filter::Bare(action)) = filter;
+                if let Action::Workflow(target) = action {
+                    if !workflows.contains_key(target) {
+                        return Err(WorkflowError::Undefined {
+                            workflow: key.to_string(),
+                            target: target.clone(),
+                        });
+                    }
+                }
+            }
+            if !matches!(workflow.filters.last(), Some(Filter::Bare(_))) {
+                return Err(WorkflowError::MissingFallback(key.to_string()));
+            }
+            for (idx, coverage) in workflow.coverage().into_iter().enumerate() {
+                if coverage == Coverage::Never {
+                    unreachable_rules.push((key.to_string(), idx));
+                }
+            }
+        }
+        if !workflows.contains_key("in") {
+            return Err(WorkflowError::MissingStart);
+        }
+
+        let mut graph = Self {
+            nodes: vec![Node::Accept, Node::Reject],
+            interned: HashMap::new(),
+            start: REJECT,
+            unreachable_rules,
+            unreachable_workflows: Vec::new(),
+        };
+        let mut compiled = HashMap::new();
+        graph.start = graph.compile_workflow("in", workflows, &mut compiled, &mut Vec::new())?;
+        graph.unreachable_workflows = keys
+            .into_iter()
+            .filter(|key| !compiled.contains_key(key.as_str()))
+            .cloned()
+            .collect();
+        Ok(graph)
+    }
+
+    /// depth first so `stack` holds the chain of workflows that led here, for spotting cycles
+    fn compile_workflow<'a>(
+        &mut self,
+        key: &'a str,
+        workflows: &'a HashMap<String, Workflow>,
+        compiled: &mut HashMap<&'a str, usize>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<usize, WorkflowError> {
+        if let Some(node) = compiled.get(key) {
+            return Ok(*node);
+        }
+        if let Some(pos) = stack.iter().position(|on_stack| *on_stack == key) {
+            let mut cycle = stack[pos..]
+                .iter()
+                .map(|key| key.to_string())
+                .collect::<Vec<String>>();
+            cycle.push(key.to_string());
+            return Err(WorkflowError::Cycle(cycle));
+        }
+        stack.push(key);
+        let workflow = &workflows[key];
+        let coverage = workflow.coverage();
+        // built back to front, so whatever a rule falls through to is already compiled. the
+        // fallback always overwrites this, since it's always last
+        let mut node = REJECT;
+        for (filter, coverage) in workflow.filters.iter().zip(coverage).rev() {
+            node = match (filter, coverage) {
+                (_, Coverage::Never) => continue,
+                (Filter::Bare(action), _)
+                | (Filter::GreaterThan { action, .. }, Coverage::Always)
+                | (Filter::LessThan { action, .. }, Coverage::Always) => {
+                    self.compile_action(action, workflows, compiled, stack)?
+                }
+                (Filter::GreaterThan { cutoff, action }, Coverage::Sometimes) => {
+                    let target = self.compile_action(action, workflows, compiled, stack)?;
+                    self.branch(cutoff.dimension(), cutoff.value() + 1, node, target)
+                }
+                (Filter::LessThan { cutoff, action }, Coverage::Sometimes) => {
+                    let target = self.compile_action(action, workflows, compiled, stack)?;
+                    self.branch(cutoff.dimension(), cutoff.value(), target, node)
+                }
+            };
+        }
+        stack.pop();
+        compiled.insert(key, node);
+        Ok(node)
+    }
+
+    fn compile_action<'a>(
+        &mut self,
+        action: &'a Action,
+        workflows: &'a HashMap<String, Workflow>,
+        compiled: &mut HashMap<&'a str, usize>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<usize, WorkflowError> {
+        match action {
+            Action::Accept => Ok(ACCEPT),
+            Action::Reject => Ok(REJECT),
+            Action::Workflow(key) => self.compile_workflow(key, workflows, compiled, stack),
+        }
+    }
+
+    /// a branch that goes the same way either side of the cutoff is just that way
+    fn branch(&mut self, dimension: usize, at: usize, below: usize, above: usize) -> usize {
+        if below == above {
+            return below;
+        }
+        let node = Node::Branch {
+            dimension,
+            at,
+            below,
+            above,
+        };
+        *self.interned.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+
+    fn accepts(&self, ratings: [usize; 4]) -> bool {
+        let mut node = self.start;
+        loop {
+            match self.nodes[node] {
+                Node::Accept => return true,
+                Node::Reject => return false,
+                Node::Branch {
+                    dimension,
+                    at,
+                    below,
+                    above,
+                } => {
+                    node = if ratings[dimension] < at {
+                        below
+                    } else {
+                        above
+                    }
+                }
+            }
+        }
+    }
+
+    /// how many parts with ratings inside `range` end up accepted
+    fn count_accepted(&self, range: RangeGroup) -> usize {
+        let mut accept_sum = 0;
+        let mut to_check = vec![(self.start, range)];
+        while let Some((node, range)) = to_check.pop() {
+            match self.nodes[node] {
+                Node::Accept => accept_sum += range.count(),
+                Node::Reject => {}
+                Node::Branch {
+                    dimension,
+                    at,
+                    below,
+                    above,
+                } => {
+                    let (below_range, above_range) = range.split(dimension, at);
+                    to_check.extend(below_range.map(|range| (below, range)));
+                    to_check.extend(above_range.map(|range| (above, range)));
+                }
+            }
+        }
+        accept_sum
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph workflows {\n");
+        dot.push_str(&format!(
+            "    {ACCEPT} [label=\"A\", shape=box, style=filled, fillcolor=palegreen];\n"
+        ));
+        dot.push_str(&format!(
+            "    {REJECT} [label=\"R\", shape=box, style=filled, fillcolor=lightpink];\n"
+        ));
+        // only what's reachable from the start, compiling can leave unused branches behind
+        let mut seen = HashSet::from([self.start]);
+        let mut to_visit = vec![self.start];
+        while let Some(node) = to_visit.pop() {
+            let Node::Branch {
+                dimension,
+                at,
+                below,
+                above,
+            } = self.nodes[node]
+            else {
+                continue;
+            };
+            let rating = ['x', 'm', 'a', 's'][dimension];
+            let shape = if node == self.start {
+                ", shape=doublecircle"
+            } else {
+                ""
+            };
+            dot.push_str(&format!("    {node} [label=\"{rating}<{at}\"{shape}];\n"));
+            dot.push_str(&format!("    {node} -> {below} [label=\"yes\"];\n"));
+            dot.push_str(&format!("    {node} -> {above} [label=\"no\"];\n"));
+            for next in [below, above] {
+                if seen.insert(next) {
+                    to_visit.push(next);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[derive(Debug)]
+enum WorkflowError {
+    MissingStart,
+    Undefined {
+        workflow: String,
+        target: String,
+    },
+    MissingFallback(String),
+    /// the workflows in the loop, first one repeated at the end
+    Cycle(Vec<String>),
+}
+
+impl Display for WorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkflowError::MissingStart => write!(f, "there's no in workflow to start from"),
+            WorkflowError::Undefined { workflow, target } => {
+                write!(
+                    f,
+                    "workflow {workflow} sends parts to undefined workflow {target}"
+                )
+            }
+            WorkflowError::MissingFallback(workflow) => {
+                write!(f, "workflow {workflow} doesn't end in a fallback rule")
+            }
+            WorkflowError::Cycle(cycle) => {
+                write!(f, "workflows loop forever: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+/// an inclusive `(start, end)` range along each of `N` axes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HyperRectangle<const N: usize> {
+    ranges: [(usize, usize); N],
+}
+
+/// one range per rating, in `xmas` order
+type RangeGroup = HyperRectangle<4>;
+
+impl<const N: usize> HyperRectangle<N> {
+    fn new(ranges: [(usize, usize); N]) -> Self {
+        Self { ranges }
+    }
+
     fn count(&self) -> usize {
-        let x_count = self.x.1 - self.x.0 + 1;
-        let m_count = self.m.1 - self.m.0 + 1;
-        let a_count = self.a.1 - self.a.0 + 1;
-        let s_count = self.s.1 - self.s.0 + 1;
-        dbg!(x_count, m_count, a_count, s_count);
-        x_count * m_count * a_count * s_count
+        self.ranges
+            .iter()
+            .map(|(start, end)| end + 1 - start)
+            .product()
+    }
+
+    /// cuts along `dimension` into the parts below `at` and from `at` up, either of which can
+    /// be empty
+    fn split(&self, dimension: usize, at: usize) -> (Option<Self>, Option<Self>) {
+        let (start, end) = self.ranges[dimension];
+        if at <= start {
+            return (None, Some(self.clone()));
+        }
+        if at > end {
+            return (Some(self.clone()), None);
+        }
+        let mut below = self.clone();
+        below.ranges[dimension] = (start, at - 1);
+        let mut above = self.clone();
+        above.ranges[dimension] = (at, end);
+        (Some(below), Some(above))
+    }
+
+    /// every point inside, first axis changing slowest
+    fn points(&self) -> impl Iterator<Item = [usize; N]> + '_ {
+        let total = self.count();
+        (0..total).map(move |mut idx| {
+            let mut point = [0; N];
+            for dim in (0..N).rev() {
+                let (start, end) = self.ranges[dim];
+                let len = end + 1 - start;
+                point[dim] = start + idx % len;
+                idx /= len;
+            }
+            point
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}";
+
+    fn workflows() -> HashMap<String, Workflow> {
+        Day19::new(SAMPLE.to_string(), Params::default())
+            .parse_input()
+            .1
+    }
+
+    #[test]
+    fn range_count_matches_brute_force() {
+        let workflows = workflows();
+        let graph = DecisionGraph::compile(&workflows).unwrap();
+        let domains = [
+            // too low to reach any of the cutoffs
+            [(1, 10); 4],
+            // straddles x<1416, m<1801, a<2006 and s<1351
+            [(1410, 1420), (1795, 1805), (2000, 2010), (1345, 1355)],
+            // straddles x>2440, m>2090, a>1716 and s<537
+            [(2435, 2445), (2085, 2095), (1710, 1720), (530, 545)],
+            // either side of s>2770 and m<1801, where s=2770 is only accepted if m<1801
+            [(2662, 2663), (1800, 1801), (3333, 3334), (2770, 2771)],
+        ];
+        for ranges in domains {
+            let domain = RangeGroup::new(ranges);
+            let brute_force = Day19::brute_force_count(&workflows, &domain);
+            assert_eq!(
+                Some(Day19::count_accepted(&workflows, domain.clone())),
+                brute_force,
+                "workflows over {ranges:?}"
+            );
+            assert_eq!(
+                Some(graph.count_accepted(domain)),
+                brute_force,
+                "decision graph over {ranges:?}"
+            );
+        }
+    }
+
+    fn compile(input: &str) -> Result<DecisionGraph, WorkflowError> {
+        let workflows = Day19::new(input.to_string(), Params::default())
+            .parse_input()
+            .1;
+        DecisionGraph::compile(&workflows)
+    }
+
+    #[test]
+    fn undefined_workflow() {
+        let err = compile("in{x<5:zz,A}").unwrap_err();
+        assert!(
+            matches!(&err, WorkflowError::Undefined { workflow, target } if workflow == "in" && target == "zz"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn missing_fallback() {
+        let err = compile("in{x<5:a,R}\na{m>10:A}").unwrap_err();
+        assert!(
+            matches!(&err, WorkflowError::MissingFallback(workflow) if workflow == "a"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let err = compile("in{x<5:a,b}\na{A}\nb{m<10:in,A}").unwrap_err();
+        assert!(
+            matches!(&err, WorkflowError::Cycle(cycle) if cycle == &["in", "b", "in"]),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn missing_start() {
+        let err = compile("a{x<5:A,R}").unwrap_err();
+        assert!(matches!(err, WorkflowError::MissingStart), "{err:?}");
+    }
+
+    #[test]
+    fn unreachable_rules() {
+        // x<3 is already caught by x<5, x>0 catches everything left, so the fallback never runs
+        let graph = compile("in{x<5:A,x<3:R,x>0:b,R}\nb{m<10:A,m<20:A,A}").unwrap();
+        assert_eq!(
+            graph.unreachable_rules,
+            vec![("in".to_string(), 1), ("in".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn unreachable_workflows() {
+        let graph = compile("in{x<5:A,b}\nb{R}\nc{A}\nd{c}").unwrap();
+        assert_eq!(graph.unreachable_workflows, vec!["c", "d"]);
+    }
+
+    #[test]
+    fn rules_that_go_the_same_way_collapse() {
+        // lnx accepts either way, so in only needs to branch on x
+        let graph = compile("in{x<5:lnx,R}\nlnx{m>1548:A,A}").unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(
+            graph.nodes[graph.start],
+            Node::Branch {
+                dimension: 0,
+                at: 5,
+                below: ACCEPT,
+                above: REJECT,
+            }
+        );
+        // in the sample that makes qs accept either way too, so neither of their cutoffs is left
+        let sample = compile(SAMPLE).unwrap();
+        assert!(!sample.nodes.iter().any(|node| matches!(
+            node,
+            Node::Branch {
+                dimension: 1,
+                at: 1549,
+                ..
+            } | Node::Branch {
+                dimension: 3,
+                at: 3449,
+                ..
+            }
+        )));
     }
 }
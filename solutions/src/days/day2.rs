@@ -1,21 +1,34 @@
 use crate::days::get_lines;
 
-use super::Day;
+use super::{Day, Params};
 
 pub struct Day2 {
     input: String,
+    params: Params,
 }
 
 impl Day2 {
-    pub fn new(input: String) -> Day2 {
-        Day2 { input }
+    /// the `--param` keys this day reads
+    pub const PARAM_KEYS: &[&str] = &["max_red", "max_green", "max_blue"];
+
+    pub fn new(input: String, params: Params) -> Day2 {
+        Day2 { input, params }
+    }
+
+    /// cube limits, overridable with `max_red`, `max_green` and `max_blue`
+    fn limits(&self) -> GameLimits {
+        GameLimits::new(
+            self.params.get("max_red", 12),
+            self.params.get("max_green", 13),
+            self.params.get("max_blue", 14),
+        )
     }
 }
 
 impl Day for Day2 {
     fn part1(&self) -> String {
         let lines = get_lines(&self.input);
-        let limits = GameLimits::new(12, 13, 14);
+        let limits = self.limits();
         cfg_if::cfg_if! {
             if #[cfg(debug_assertions)] {
                 let games = lines
@@ -51,7 +64,7 @@ impl Day for Day2 {
 
     fn part2(&self) -> String {
         let lines = get_lines(&self.input);
-        let limits = GameLimits::new(12, 13, 14);
+        let limits = self.limits();
         cfg_if::cfg_if! {
             if #[cfg(debug_assertions)] {
                 let games = lines
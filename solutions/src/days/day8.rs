@@ -1,5 +1,5 @@
-use num::integer::lcm;
-use std::collections::{HashMap, VecDeque};
+use num::integer::{ExtendedGcd, Integer};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::{get_lines, Day};
 
@@ -63,32 +63,179 @@ impl Day8 {
         sum
     }
 
-    fn get_steps_to_end_z(
-        instructions: &mut VecDeque<char>,
+    /// exports the network as graphviz DOT, start nodes are green, end nodes are red, and the
+    /// edges walked from each start node to its first end node are bold and numbered by step
+    pub fn network_to_dot(&self) -> String {
+        let (instructions, map) = self.parse_input();
+        let instructions = Vec::from(instructions);
+        let mut keys = map.keys().copied().collect::<Vec<&str>>();
+        keys.sort();
+
+        // (from, direction) -> first step it was walked on
+        let mut traversed = HashMap::new();
+        for start in keys.iter().filter(|key| key.ends_with('A')) {
+            let mut seen = HashSet::new();
+            let mut next_key = *start;
+            let mut step = 0;
+            while !next_key.ends_with('Z') && seen.insert((next_key, step % instructions.len())) {
+                let direction = instructions[step % instructions.len()];
+                traversed.entry((next_key, direction)).or_insert(step + 1);
+                let (left, right) = map.get(next_key).unwrap();
+                next_key = match direction {
+                    'R' => right,
+                    'L' => left,
+                    _ => panic!("Invalid direction"),
+                };
+                step += 1;
+            }
+        }
+
+        let mut dot = String::from("digraph network {\n");
+        for key in &keys {
+            if key.ends_with('A') {
+                dot.push_str(&format!(
+                    "    \"{key}\" [style=filled, fillcolor=palegreen];\n"
+                ));
+            } else if key.ends_with('Z') {
+                dot.push_str(&format!(
+                    "    \"{key}\" [style=filled, fillcolor=salmon];\n"
+                ));
+            }
+        }
+        for key in &keys {
+            let (left, right) = map.get(key).unwrap();
+            for (direction, next_key) in [('L', left), ('R', right)] {
+                match traversed.get(&(*key, direction)) {
+                    Some(step) => dot.push_str(&format!(
+                        "    \"{key}\" -> \"{next_key}\" [label=\"{direction} #{step}\", penwidth=2, color=blue];\n"
+                    )),
+                    None => dot.push_str(&format!(
+                        "    \"{key}\" -> \"{next_key}\" [label=\"{direction}\"];\n"
+                    )),
+                }
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// walks a single ghost until its (node, instruction index) state repeats, recording
+    /// every step where it sits on a node ending in `Z`
+    fn get_ghost_cycle(
+        instructions: &[char],
         map: &HashMap<&str, (&str, &str)>,
         starting_key: &str,
-    ) -> usize {
+    ) -> GhostCycle {
+        let mut seen = HashMap::new();
+        let mut z_steps = Vec::new();
         let mut next_key = starting_key;
-        let mut sum = 0;
-        while let Some(direction) = instructions.pop_front() {
-            match direction {
-                'R' => {
-                    let (_, right) = map.get(next_key).unwrap();
-                    next_key = right;
-                }
-                'L' => {
-                    let (left, _) = map.get(next_key).unwrap();
-                    next_key = left;
-                }
-                _ => panic!("Invalid direction"),
+        let mut step = 0;
+        let (cycle_start, cycle_len) = loop {
+            let instruction_idx = step % instructions.len();
+            if let Some(first_seen) = seen.get(&(next_key, instruction_idx)) {
+                break (*first_seen, step - first_seen);
             }
-            sum += 1;
+            seen.insert((next_key, instruction_idx), step);
             if next_key.ends_with('Z') {
-                break;
+                z_steps.push(step);
             }
-            instructions.push_back(direction);
+            let (left, right) = map.get(next_key).unwrap();
+            next_key = match instructions[instruction_idx] {
+                'R' => right,
+                'L' => left,
+                _ => panic!("Invalid direction"),
+            };
+            step += 1;
+        };
+        let (pre_cycle_hits, cycle_hits) =
+            z_steps.into_iter().partition(|step| *step < cycle_start);
+        GhostCycle {
+            pre_cycle_hits,
+            cycle_start,
+            cycle_len,
+            cycle_hits,
         }
-        sum
+    }
+
+    /// finds the first step where every ghost is on a `Z` node, if there is one
+    fn solve_ghosts(ghosts: &[GhostCycle]) -> Option<i128> {
+        if ghosts.is_empty() {
+            return None;
+        }
+        // hits before a ghost enters its loop only happen once, so check them directly
+        let mut first_step = ghosts
+            .iter()
+            .flat_map(|ghost| ghost.pre_cycle_hits.iter())
+            .map(|step| *step as i128)
+            .filter(|step| *step > 0 && ghosts.iter().all(|ghost| ghost.hits_at(*step)))
+            .min();
+
+        // otherwise every ghost is in its loop, so each one picks a congruence to satisfy
+        let mut congruences = vec![(0, 1)];
+        for ghost in ghosts {
+            let mut next_congruences = Vec::new();
+            for congruence in &congruences {
+                for hit in &ghost.cycle_hits {
+                    let ghost_congruence = (*hit as i128, ghost.cycle_len as i128);
+                    if let Some(combined) = Self::combine_congruences(*congruence, ghost_congruence)
+                    {
+                        next_congruences.push(combined);
+                    }
+                }
+            }
+            next_congruences.sort();
+            next_congruences.dedup();
+            congruences = next_congruences;
+        }
+
+        let min_step = ghosts
+            .iter()
+            .map(|ghost| ghost.cycle_start as i128)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (remainder, modulus) in congruences {
+            let mut step = remainder;
+            if step < min_step {
+                step += (min_step - step + modulus - 1) / modulus * modulus;
+            }
+            first_step = Some(first_step.map_or(step, |first| first.min(step)));
+        }
+        first_step
+    }
+
+    /// CRT for moduli that are not necessarily coprime, returns `None` if the two can't agree
+    fn combine_congruences(
+        (rem_a, mod_a): (i128, i128),
+        (rem_b, mod_b): (i128, i128),
+    ) -> Option<(i128, i128)> {
+        let ExtendedGcd { gcd, x, .. } = mod_a.extended_gcd(&mod_b);
+        if (rem_b - rem_a) % gcd != 0 {
+            return None;
+        }
+        let lcm = mod_a / gcd * mod_b;
+        // mod_a * x == gcd (mod mod_b), so this many steps of mod_a lands on rem_b
+        let steps = ((rem_b - rem_a) / gcd * x).rem_euclid(mod_b / gcd);
+        Some(((rem_a + mod_a * steps).rem_euclid(lcm), lcm))
+    }
+}
+
+/// where a ghost lands on a `Z` node, hits in `cycle_hits` repeat every `cycle_len` steps
+struct GhostCycle {
+    pre_cycle_hits: Vec<usize>,
+    cycle_start: usize,
+    cycle_len: usize,
+    cycle_hits: Vec<usize>,
+}
+
+impl GhostCycle {
+    fn hits_at(&self, step: i128) -> bool {
+        let cycle_start = self.cycle_start as i128;
+        if step < cycle_start {
+            return self.pre_cycle_hits.contains(&(step as usize));
+        }
+        let cycle_step = cycle_start + (step - cycle_start) % self.cycle_len as i128;
+        self.cycle_hits.contains(&(cycle_step as usize))
     }
 }
 
@@ -101,19 +248,180 @@ impl Day for Day8 {
     fn part2(&self) -> String {
         let start_time = std::time::Instant::now();
         let (instructions, map) = self.parse_input();
-        let mut a_enders = Vec::new();
-        for key in map.keys() {
-            if key.ends_with('A') {
-                a_enders.push(key);
-            }
-        }
-        let sums = a_enders
-            .iter()
-            .map(|key| Self::get_steps_to_end_z(&mut instructions.clone(), &map, key))
-            .collect::<Vec<usize>>();
-        let lcm = sums.iter().fold(1, |acc, x| lcm(acc, *x));
+        let instructions = Vec::from(instructions);
+        let ghosts = map
+            .keys()
+            .filter(|key| key.ends_with('A'))
+            .map(|key| Self::get_ghost_cycle(&instructions, &map, key))
+            .collect::<Vec<GhostCycle>>();
+        let first_step = Self::solve_ghosts(&ghosts);
         let elapsed = start_time.elapsed();
         println!("Elapsed: {:?}", elapsed);
-        format!("{lcm}")
+        match first_step {
+            Some(step) => format!("{step}"),
+            None => "no solution".to_string(),
+        }
+    }
+
+    fn render(&self) -> Option<String> {
+        Some(self.network_to_dot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a network where every node goes to the same place whichever way it turns
+    fn network(instructions: &str, edges: &[(&str, &str)]) -> String {
+        let nodes = edges
+            .iter()
+            .map(|(from, to)| format!("{from} = ({to}, {to})"))
+            .collect::<Vec<String>>();
+        format!("{instructions}\n\n{}", nodes.join("\n"))
+    }
+
+    fn solve(input: &str) -> Option<i128> {
+        let day = Day8::new(input.to_string());
+        let (instructions, map) = day.parse_input();
+        let instructions = Vec::from(instructions);
+        let ghosts = map
+            .keys()
+            .filter(|key| key.ends_with('A'))
+            .map(|key| Day8::get_ghost_cycle(&instructions, &map, key))
+            .collect::<Vec<GhostCycle>>();
+        Day8::solve_ghosts(&ghosts)
+    }
+
+    /// moves every ghost together one step at a time, up to `limit` steps
+    fn step_together(input: &str, limit: usize) -> Option<i128> {
+        let day = Day8::new(input.to_string());
+        let (instructions, map) = day.parse_input();
+        let mut ghosts = map
+            .keys()
+            .filter(|key| key.ends_with('A'))
+            .copied()
+            .collect::<Vec<&str>>();
+        if ghosts.is_empty() {
+            return None;
+        }
+        for step in 1..=limit {
+            let direction = instructions[(step - 1) % instructions.len()];
+            for ghost in ghosts.iter_mut() {
+                let (left, right) = map.get(ghost).unwrap();
+                *ghost = if direction == 'L' { left } else { right };
+            }
+            if ghosts.iter().all(|ghost| ghost.ends_with('Z')) {
+                return Some(step as i128);
+            }
+        }
+        None
+    }
+
+    fn assert_matches_stepping(input: &str, expected: Option<i128>) {
+        assert_eq!(step_together(input, 1000), expected);
+        assert_eq!(solve(input), expected);
+    }
+
+    #[test]
+    fn sample() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        assert_matches_stepping(input, Some(6));
+    }
+
+    #[test]
+    fn hits_before_the_loop() {
+        // both ghosts only ever pass through Z on their way into a loop without one
+        let input = network(
+            "L",
+            &[
+                ("11A", "11Z"),
+                ("11Z", "11B"),
+                ("11B", "11C"),
+                ("11C", "11B"),
+                ("22A", "22Z"),
+                ("22Z", "22B"),
+                ("22B", "22B"),
+            ],
+        );
+        assert_matches_stepping(&input, Some(1));
+    }
+
+    #[test]
+    fn several_hits_per_loop() {
+        // the first ghost is on a Z every other step, the second every fifth
+        let input = network(
+            "L",
+            &[
+                ("11A", "11B"),
+                ("11B", "11Z"),
+                ("11Z", "11C"),
+                ("11C", "12Z"),
+                ("12Z", "11B"),
+                ("22A", "22B"),
+                ("22B", "22C"),
+                ("22C", "22Z"),
+                ("22Z", "22D"),
+                ("22D", "22A"),
+            ],
+        );
+        assert_matches_stepping(&input, Some(8));
+    }
+
+    #[test]
+    fn moduli_that_share_a_factor() {
+        // 1 mod 4 and 3 mod 6
+        let input = network(
+            "L",
+            &[
+                ("11A", "11Z"),
+                ("11Z", "11B"),
+                ("11B", "11C"),
+                ("11C", "11A"),
+                ("22A", "22B"),
+                ("22B", "22C"),
+                ("22C", "22Z"),
+                ("22Z", "22D"),
+                ("22D", "22E"),
+                ("22E", "22A"),
+            ],
+        );
+        assert_matches_stepping(&input, Some(9));
+    }
+
+    #[test]
+    fn no_solution() {
+        // 1 mod 4 is always odd and 2 mod 6 is always even
+        let input = network(
+            "L",
+            &[
+                ("11A", "11Z"),
+                ("11Z", "11B"),
+                ("11B", "11C"),
+                ("11C", "11A"),
+                ("22A", "22B"),
+                ("22B", "22Z"),
+                ("22Z", "22C"),
+                ("22C", "22D"),
+                ("22D", "22E"),
+                ("22E", "22A"),
+            ],
+        );
+        assert_matches_stepping(&input, None);
+    }
+
+    #[test]
+    fn no_ghosts() {
+        let input = network("L", &[("11B", "11Z"), ("11Z", "11B")]);
+        assert_matches_stepping(&input, None);
     }
 }
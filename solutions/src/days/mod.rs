@@ -1,4 +1,4 @@
-use std::fs;
+use std::{collections::HashMap, fmt::Debug, fs, str::FromStr};
 
 use self::{
     day1::Day1, day10::Day10, day11::Day11, day12::Day12, day13::Day13, day14::Day14, day15::Day15,
@@ -31,6 +31,57 @@ pub mod day9;
 pub trait Day {
     fn part1(&self) -> String;
     fn part2(&self) -> String;
+    /// an optional picture of the puzzle, printed by the runner with `--render`, or written to
+    /// the `--output` file
+    fn render(&self) -> Option<String> {
+        None
+    }
+}
+
+/// `key=value` pairs passed to the runner with `--param`, each day reads the ones it knows about
+#[derive(Debug, Clone, Default)]
+pub struct Params(HashMap<String, String>);
+
+impl Params {
+    /// the value for `key` parsed as `T`, or `default` if it wasn't passed
+    pub fn get<T>(&self, key: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Debug,
+    {
+        match self.0.get(key) {
+            Some(value) => value
+                .parse()
+                .unwrap_or_else(|err| panic!("Invalid value {value} for param {key}: {err:?}")),
+            None => default,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Params(iter.into_iter().collect())
+    }
+}
+
+/// the `--param` keys `day` reads, empty for days without any
+pub fn param_keys(day: i8) -> &'static [&'static str] {
+    match day {
+        2 => Day2::PARAM_KEYS,
+        10 => Day10::PARAM_KEYS,
+        11 => Day11::PARAM_KEYS,
+        12 => Day12::PARAM_KEYS,
+        14 => Day14::PARAM_KEYS,
+        15 => Day15::PARAM_KEYS,
+        16 => Day16::PARAM_KEYS,
+        17 => Day17::PARAM_KEYS,
+        19 => Day19::PARAM_KEYS,
+        _ => &[],
+    }
 }
 
 pub fn get_day_input(day: &str) -> String {
@@ -42,10 +93,10 @@ fn get_lines(input: &str) -> Vec<&str> {
     input.lines().collect::<Vec<&str>>()
 }
 
-pub fn day_builder(day: i8, input_name: &str) -> Box<dyn Day> {
+pub fn day_builder(day: i8, input_name: &str, params: &Params) -> Box<dyn Day> {
     match day {
         1 => Box::new(Day1::new(get_day_input(input_name))) as Box<dyn Day>,
-        2 => Box::new(Day2::new(get_day_input(input_name))) as Box<dyn Day>,
+        2 => Box::new(Day2::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
         3 => Box::new(Day3::new(get_day_input(input_name))) as Box<dyn Day>,
         4 => Box::new(Day4::new(get_day_input(input_name))) as Box<dyn Day>,
         5 => Box::new(Day5::new(get_day_input(input_name))) as Box<dyn Day>,
@@ -53,16 +104,16 @@ pub fn day_builder(day: i8, input_name: &str) -> Box<dyn Day> {
         7 => Box::new(Day7::new(get_day_input(input_name))) as Box<dyn Day>,
         8 => Box::new(Day8::new(get_day_input(input_name))) as Box<dyn Day>,
         9 => Box::new(Day9::new(get_day_input(input_name))) as Box<dyn Day>,
-        10 => Box::new(Day10::new(get_day_input(input_name))) as Box<dyn Day>,
-        11 => Box::new(Day11::new(get_day_input(input_name))) as Box<dyn Day>,
-        12 => Box::new(Day12::new(get_day_input(input_name))) as Box<dyn Day>,
+        10 => Box::new(Day10::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
+        11 => Box::new(Day11::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
+        12 => Box::new(Day12::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
         13 => Box::new(Day13::new(get_day_input(input_name))) as Box<dyn Day>,
-        14 => Box::new(Day14::new(get_day_input(input_name))) as Box<dyn Day>,
-        15 => Box::new(Day15::new(get_day_input(input_name))) as Box<dyn Day>,
-        16 => Box::new(Day16::new(get_day_input(input_name))) as Box<dyn Day>,
-        17 => Box::new(Day17::new(get_day_input(input_name))) as Box<dyn Day>,
+        14 => Box::new(Day14::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
+        15 => Box::new(Day15::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
+        16 => Box::new(Day16::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
+        17 => Box::new(Day17::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
         18 => Box::new(Day18::new(get_day_input(input_name))) as Box<dyn Day>,
-        19 => Box::new(Day19::new(get_day_input(input_name))) as Box<dyn Day>,
+        19 => Box::new(Day19::new(get_day_input(input_name), params.clone())) as Box<dyn Day>,
         // 20 => Box::new(Day20::new(get_day_input(input_name))) as Box<dyn Day>,
         // 21 => Box::new(Day21::new(get_day_input(input_name))) as Box<dyn Day>,
         // 22 => Box::new(Day22::new(get_day_input(input_name))) as Box<dyn Day>,
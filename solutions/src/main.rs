@@ -1,9 +1,12 @@
+use std::{fs, path::PathBuf};
+
 use clap::Parser;
-use days::{day_builder, Day};
+use days::{day_builder, param_keys, Day, Params};
 
 use crate::days::CURRENT_DAY;
 
 pub mod days;
+pub mod utils;
 
 #[derive(Parser, Debug)]
 struct Cli {
@@ -11,33 +14,107 @@ struct Cli {
     day: Option<i8>,
     /// the part to run
     part: Option<u8>,
+    /// a `key=value` setting for the day, can be passed more than once
+    #[arg(long = "param", value_parser = parse_param, long_help = param_help())]
+    params: Vec<(String, String)>,
+    /// render the day instead of solving it
+    #[arg(long)]
+    render: bool,
+    /// write the render to this file instead of printing it, without any terminal colours
+    #[arg(long, requires = "render")]
+    output: Option<PathBuf>,
+}
+
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = param.split_once('=') else {
+        return Err(format!("expected key=value, got {param}"));
+    };
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// the `--param` help with every day's keys listed
+fn param_help() -> String {
+    let mut help = String::from(
+        "a `key=value` setting for the day, can be passed more than once\n\nkeys each day reads:",
+    );
+    for day in 1..=CURRENT_DAY {
+        let keys = param_keys(day);
+        if !keys.is_empty() {
+            help.push_str(&format!("\n  day {day}: {}", keys.join(", ")));
+        }
+    }
+    help
+}
+
+/// drops ANSI colour codes, so a render written to a file is plain text
+fn strip_ansi(render: &str) -> String {
+    let mut plain = String::with_capacity(render.len());
+    let mut chars = render.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // escape sequences run up to and including their final letter
+            chars.find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
 }
 
 fn main() {
     let args = Cli::parse();
     let day = args.day.unwrap_or(-1);
+    let params = args.params.iter().cloned().collect::<Params>();
+    let selected_days = if day == -1 {
+        (1..=CURRENT_DAY).collect::<Vec<i8>>()
+    } else {
+        vec![day]
+    };
+    for key in params.keys() {
+        if !selected_days
+            .iter()
+            .any(|day| param_keys(*day).contains(&key))
+        {
+            match selected_days.as_slice() {
+                [day] => eprintln!(
+                    "warning: day {day} doesn't read param {key}, it reads [{}]",
+                    param_keys(*day).join(", ")
+                ),
+                _ => eprintln!("warning: no day reads param {key}, see --help for the keys"),
+            }
+        }
+    }
     let mut days: Vec<(i8, Box<dyn Day>)> = Vec::new();
     cfg_if::cfg_if! {
         if #[cfg(debug_assertions)] {
             if day == -1 {
                 for day_num in 1..=CURRENT_DAY {
-                    days.push((day_num, day_builder(day_num, &format!("day{}_test", day_num))));
+                    days.push((day_num, day_builder(day_num, &format!("day{}_test", day_num), &params)));
                 }
             } else {
-                days.push((day, day_builder(day, &format!("day{}_test", day))));
+                days.push((day, day_builder(day, &format!("day{}_test", day), &params)));
             }
         }
         else {
             if day == -1 {
                 for day_num in 1..=CURRENT_DAY {
-                    days.push((day_num, day_builder(day_num, &format!("day{}", day_num))));
+                    days.push((day_num, day_builder(day_num, &format!("day{}", day_num), &params)));
                 }
             } else {
-                days.push((day, day_builder(day, &format!("day{}", day))));
+                days.push((day, day_builder(day, &format!("day{}", day), &params)));
             }
         }
     }
     for (idx, day) in days {
+        if args.render {
+            match (day.render(), &args.output) {
+                (Some(render), Some(path)) => fs::write(path, strip_ansi(&render))
+                    .expect("Something went wrong writing the render"),
+                (Some(render), None) => println!("{render}"),
+                (None, _) => println!("Day {} has nothing to render", idx),
+            }
+            continue;
+        }
         println!("Day {}", idx);
         if let Some(part) = args.part {
             match part {
@@ -52,3 +129,16 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_colour_codes() {
+        assert_eq!(
+            strip_ansi("\x1b[1;33m╔\x1b[0m.\x1b[2m│\x1b[0mI\n"),
+            "╔.│I\n"
+        );
+    }
+}
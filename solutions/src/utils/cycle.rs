@@ -0,0 +1,163 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a deterministic state machine starts repeating: after `prefix` steps it enters a loop
+/// that comes back around every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// the earliest step with the same state as step `n`
+    pub fn first_equivalent(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// steps until a state repeats, keeping every state seen in a hash index, returns the cycle
+/// and the history of states from `start` up to just before the first repeat
+pub fn find_cycle<T, F>(start: T, step: F) -> (Cycle, Vec<T>)
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    let (cycle, history, _) = walk(start, step, usize::MAX);
+    (cycle.expect("no repeat in usize::MAX steps"), history)
+}
+
+/// steps until a state repeats or `limit` steps have been taken, returns the cycle if one was
+/// found, the history of states before stopping, and the state it stopped on
+fn walk<T, F>(start: T, mut step: F, limit: usize) -> (Option<Cycle>, Vec<T>, T)
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    let mut state = start;
+    while history.len() < limit {
+        if let Some(first_idx) = seen.get(&state) {
+            let cycle = Cycle {
+                prefix: *first_idx,
+                period: history.len() - first_idx,
+            };
+            return (Some(cycle), history, state);
+        }
+        seen.insert(state.clone(), history.len());
+        let next_state = step(&state);
+        history.push(state);
+        state = next_state;
+    }
+    (None, history, state)
+}
+
+/// Brent's algorithm, finds the same cycle as [`find_cycle`] while only ever holding two states,
+/// at the cost of stepping more times
+pub fn brent<T, F>(start: T, mut step: F) -> Cycle
+where
+    T: Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    // find the period by teleporting the tortoise to the hare at every power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // then walk two states `period` apart until they meet at the start of the loop
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// the state after `n` steps from `start`, skipping over whole loops once a repeat is found
+pub fn fast_forward<T, F>(start: T, step: F, n: usize) -> T
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    match walk(start, step, n) {
+        (Some(cycle), mut history, _) => history.swap_remove(cycle.first_equivalent(n)),
+        (None, _, state) => state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// counts 0, 1, 2, then loops around 3..=9 forever
+    fn step(n: &usize) -> usize {
+        if *n == 9 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        prefix: 3,
+        period: 7,
+    };
+
+    fn walk_naively(n: usize) -> usize {
+        (0..n).fold(0, |state, _| step(&state))
+    }
+
+    #[test]
+    fn first_equivalent() {
+        assert_eq!(CYCLE.first_equivalent(2), 2);
+        assert_eq!(CYCLE.first_equivalent(3), 3);
+        assert_eq!(CYCLE.first_equivalent(10), 3);
+        assert_eq!(CYCLE.first_equivalent(1000), 6);
+    }
+
+    #[test]
+    fn hashed_and_brent_agree() {
+        let (cycle, history) = find_cycle(0, step);
+        assert_eq!(cycle, CYCLE);
+        assert_eq!(history, (0..=9).collect::<Vec<usize>>());
+        assert_eq!(brent(0, step), CYCLE);
+    }
+
+    #[test]
+    fn cycle_from_inside_the_loop() {
+        let expected = Cycle {
+            prefix: 0,
+            period: 7,
+        };
+        assert_eq!(find_cycle(5, step).0, expected);
+        assert_eq!(brent(5, step), expected);
+    }
+
+    #[test]
+    fn fast_forward_matches_stepping() {
+        // nothing, inside the prefix, the first repeat, and far along the loop
+        for n in [0, 1, 2, 3, 9, 10, 11, 1000, 1_000_003] {
+            assert_eq!(fast_forward(0, step, n), walk_naively(n), "{n} steps");
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod cycle;
+pub mod parallel;
+pub mod polygon;
+pub mod sequence;
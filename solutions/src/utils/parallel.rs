@@ -0,0 +1,22 @@
+use std::{iter::Sum, thread};
+
+/// Splits `items` into one chunk per available core, runs `sum_chunk` on each chunk in its own
+/// thread and adds up the results.
+pub fn sum_chunks<T, S, F>(items: &[T], sum_chunk: F) -> S
+where
+    T: Sync,
+    S: Send + Sum,
+    F: Fn(&[T]) -> S + Sync,
+{
+    let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = items.len().div_ceil(num_threads).max(1);
+    thread::scope(|scope| {
+        items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| sum_chunk(chunk)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    })
+}
@@ -0,0 +1,250 @@
+use num::integer::gcd;
+
+pub type Vertex = (i64, i64);
+
+/// A closed polygon on the integer lattice, the last vertex joins back up to the first.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<Vertex>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vertex>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    /// follows each (dx, dy) move from `start`, the final move should land back on `start`
+    pub fn from_moves(start: Vertex, moves: impl IntoIterator<Item = Vertex>) -> Polygon {
+        let mut vertices = vec![start];
+        let mut current = start;
+        for (dx, dy) in moves {
+            current = (current.0 + dx, current.1 + dy);
+            vertices.push(current);
+        }
+        if vertices.len() > 1 && vertices.last() == Some(&start) {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// every side as (from, to)
+    pub fn edges(&self) -> impl Iterator<Item = (Vertex, Vertex)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(from, to)| (*from, *to))
+    }
+
+    /// shoelace formula, doubled so it stays an integer
+    pub fn twice_area(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum::<i64>()
+            .abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.twice_area() as f64 / 2.0
+    }
+
+    /// euclidean length of the sides
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| (((b.0 - a.0).pow(2) + (b.1 - a.1).pow(2)) as f64).sqrt())
+            .sum()
+    }
+
+    /// lattice points on the sides, for rectilinear polygons this is the perimeter
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| gcd((b.0 - a.0).abs(), (b.1 - a.1).abs()))
+            .sum()
+    }
+
+    /// lattice points strictly inside, from Pick's theorem: area = interior + boundary / 2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.twice_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// (min corner, max corner), `None` if there are no vertices
+    pub fn bounding_box(&self) -> Option<(Vertex, Vertex)> {
+        let min_x = self.vertices.iter().map(|v| v.0).min()?;
+        let min_y = self.vertices.iter().map(|v| v.1).min()?;
+        let max_x = self.vertices.iter().map(|v| v.0).max()?;
+        let max_y = self.vertices.iter().map(|v| v.1).max()?;
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+
+    /// every side is horizontal or vertical
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|(a, b)| a.0 == b.0 || a.1 == b.1)
+    }
+
+    /// the first pair of sides (by index) that cross, touch, or double back over each other,
+    /// other than neighbours meeting at their shared corner. zero length sides are skipped, so
+    /// the sides either side of one still count as neighbours
+    pub fn self_intersection(&self) -> Option<(usize, usize)> {
+        let edges = self
+            .edges()
+            .enumerate()
+            .filter(|(_, (from, to))| from != to)
+            .collect::<Vec<(usize, (Vertex, Vertex))>>();
+        let len = edges.len();
+        for i in 0..len {
+            for j in i + 1..len {
+                let ((first_idx, first), (second_idx, second)) = (edges[i], edges[j]);
+                let adjacent = j == i + 1 || (i == 0 && j == len - 1);
+                let crosses = if adjacent {
+                    Self::doubles_back(first, second)
+                } else {
+                    Self::segments_intersect(first, second)
+                };
+                if crosses {
+                    return Some((first_idx, second_idx));
+                }
+            }
+        }
+        None
+    }
+
+    /// > 0 for a left turn from a -> b -> c, < 0 for a right turn, 0 if they're in a line
+    fn orientation(a: Vertex, b: Vertex, c: Vertex) -> i64 {
+        ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).signum()
+    }
+
+    /// `point` is inside the box spanned by the segment, only meaningful when they're in a line
+    fn on_segment((a, b): (Vertex, Vertex), point: Vertex) -> bool {
+        point.0 >= a.0.min(b.0)
+            && point.0 <= a.0.max(b.0)
+            && point.1 >= a.1.min(b.1)
+            && point.1 <= a.1.max(b.1)
+    }
+
+    fn segments_intersect(first: (Vertex, Vertex), second: (Vertex, Vertex)) -> bool {
+        let (a, b) = first;
+        let (c, d) = second;
+        let (o1, o2) = (Self::orientation(a, b, c), Self::orientation(a, b, d));
+        let (o3, o4) = (Self::orientation(c, d, a), Self::orientation(c, d, b));
+        if o1 != o2 && o3 != o4 {
+            return true;
+        }
+        (o1 == 0 && Self::on_segment(first, c))
+            || (o2 == 0 && Self::on_segment(first, d))
+            || (o3 == 0 && Self::on_segment(second, a))
+            || (o4 == 0 && Self::on_segment(second, b))
+    }
+
+    /// neighbouring sides overlap if they're in a line and point opposite ways
+    fn doubles_back(first: (Vertex, Vertex), second: (Vertex, Vertex)) -> bool {
+        let first_delta = (first.1 .0 - first.0 .0, first.1 .1 - first.0 .1);
+        let second_delta = (second.1 .0 - second.0 .0, second.1 .1 - second.0 .1);
+        let cross = first_delta.0 * second_delta.1 - first_delta.1 * second_delta.0;
+        let dot = first_delta.0 * second_delta.0 + first_delta.1 * second_delta.1;
+        cross == 0 && dot < 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle() -> Polygon {
+        Polygon::new(vec![(0, 0), (3, 0), (3, 2), (0, 2)])
+    }
+
+    /// the hypotenuse passes through (3, 1), (2, 2) and (1, 3), which only `gcd` picks up
+    fn right_triangle() -> Polygon {
+        Polygon::new(vec![(0, 0), (4, 0), (0, 4)])
+    }
+
+    #[test]
+    fn rectangle_counts() {
+        let rectangle = rectangle();
+        assert_eq!(rectangle.twice_area(), 12);
+        assert_eq!(rectangle.boundary_points(), 10);
+        assert_eq!(rectangle.interior_points(), 2);
+        assert_eq!(rectangle.bounding_box(), Some(((0, 0), (3, 2))));
+        assert!(rectangle.is_rectilinear());
+    }
+
+    #[test]
+    fn diagonal_side_counts() {
+        let triangle = right_triangle();
+        assert_eq!(triangle.twice_area(), 16);
+        // 4 along each leg, 4 along the hypotenuse rather than its length of 4 * sqrt(2)
+        assert_eq!(triangle.boundary_points(), 12);
+        // (1, 1), (1, 2) and (2, 1)
+        assert_eq!(triangle.interior_points(), 3);
+        assert_eq!(triangle.bounding_box(), Some(((0, 0), (4, 4))));
+        assert!(!triangle.is_rectilinear());
+    }
+
+    #[test]
+    fn orientation_does_not_matter() {
+        let mut vertices = right_triangle().vertices().to_vec();
+        vertices.reverse();
+        let clockwise = Polygon::new(vertices);
+        assert_eq!(clockwise.twice_area(), 16);
+        assert_eq!(clockwise.interior_points(), 3);
+    }
+
+    #[test]
+    fn from_moves_drops_the_closing_vertex() {
+        let polygon = Polygon::from_moves((1, 1), [(3, 0), (0, 2), (-3, 0), (0, -2)]);
+        assert_eq!(polygon.vertices(), &[(1, 1), (4, 1), (4, 3), (1, 3)]);
+        assert_eq!(polygon.twice_area(), rectangle().twice_area());
+    }
+
+    #[test]
+    fn empty() {
+        let polygon = Polygon::new(Vec::new());
+        assert_eq!(polygon.twice_area(), 0);
+        assert_eq!(polygon.boundary_points(), 0);
+        assert_eq!(polygon.bounding_box(), None);
+    }
+
+    #[test]
+    fn simple_loop_is_simple() {
+        assert_eq!(rectangle().self_intersection(), None);
+        assert_eq!(right_triangle().self_intersection(), None);
+    }
+
+    #[test]
+    fn out_and_back_doubles_back() {
+        let polygon = Polygon::from_moves((0, 0), [(1, 0), (-1, 0)]);
+        assert_eq!(polygon.self_intersection(), Some((0, 1)));
+    }
+
+    #[test]
+    fn zero_length_sides_are_skipped() {
+        // the sides either side of the zero length one meet at a corner, they don't cross
+        let polygon = Polygon::from_moves((0, 0), [(2, 0), (0, 0), (0, 2), (-2, 0), (0, -2)]);
+        assert_eq!(polygon.self_intersection(), None);
+        // but the sides either side of one can still double back
+        let polygon = Polygon::from_moves((0, 0), [(1, 0), (0, 0), (-1, 0)]);
+        assert_eq!(polygon.self_intersection(), Some((0, 2)));
+    }
+
+    #[test]
+    fn figure_eight_touches_itself() {
+        // two squares meeting only at (2, 2)
+        let polygon = Polygon::from_moves(
+            (0, 0),
+            [
+                (2, 0),
+                (0, 2),
+                (2, 0),
+                (0, 2),
+                (-2, 0),
+                (0, -2),
+                (-2, 0),
+                (0, -2),
+            ],
+        );
+        assert_eq!(polygon.self_intersection(), Some((1, 5)));
+    }
+}
@@ -0,0 +1,127 @@
+use num::{BigInt, Zero};
+
+/// The lowest degree polynomial through a sequence, stored in Newton form as the leading entry
+/// of each row of the forward difference table.
+///
+/// Every value is kept as a [`BigInt`], so fitting and extrapolating are exact.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    len: usize,
+    differences: Vec<BigInt>,
+}
+
+impl Sequence {
+    pub fn fit(values: &[i64]) -> Sequence {
+        let mut row = values
+            .iter()
+            .map(|v| BigInt::from(*v))
+            .collect::<Vec<BigInt>>();
+        let mut differences = Vec::new();
+        while !row.is_empty() && !row.iter().all(|v| v.is_zero()) {
+            differences.push(row[0].clone());
+            row = row.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        }
+        Sequence {
+            len: values.len(),
+            differences,
+        }
+    }
+
+    /// degree of the fitted polynomial, `None` if every value was 0
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    /// value of the sequence at `idx`, where `0` is the first known value and negative indices
+    /// are before it
+    pub fn value_at(&self, idx: i64) -> BigInt {
+        // sum of difference_j * (idx choose j), with the binomial built up one factor at a time
+        let idx = BigInt::from(idx);
+        let mut binomial = BigInt::from(1);
+        let mut value = BigInt::zero();
+        for (j, difference) in self.differences.iter().enumerate() {
+            value += difference * &binomial;
+            binomial = binomial * (&idx - j) / (j + 1);
+        }
+        value
+    }
+
+    /// the next `steps` values after the sequence, or the previous `-steps` values before it
+    /// (closest first) when `steps` is negative
+    pub fn extrapolate(&self, steps: i64) -> Vec<BigInt> {
+        if steps.is_negative() {
+            (1..=steps.abs()).map(|step| self.value_at(-step)).collect()
+        } else {
+            let len = self.len as i64;
+            (len..len + steps).map(|idx| self.value_at(idx)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(values: &[i64]) -> Vec<BigInt> {
+        values.iter().map(|v| BigInt::from(*v)).collect()
+    }
+
+    #[test]
+    fn day9_sample() {
+        let rows = [
+            [0, 3, 6, 9, 12, 15],
+            [1, 3, 6, 10, 15, 21],
+            [10, 13, 16, 21, 30, 45],
+        ];
+        let sequences = rows.map(|row| Sequence::fit(&row));
+        let next = sequences
+            .iter()
+            .map(|sequence| sequence.extrapolate(1).remove(0))
+            .sum::<BigInt>();
+        let previous = sequences
+            .iter()
+            .map(|sequence| sequence.extrapolate(-1).remove(0))
+            .sum::<BigInt>();
+        assert_eq!(next, BigInt::from(114));
+        assert_eq!(previous, BigInt::from(2));
+    }
+
+    #[test]
+    fn all_zero() {
+        let sequence = Sequence::fit(&[0, 0, 0, 0]);
+        assert_eq!(sequence.degree(), None);
+        assert_eq!(sequence.extrapolate(2), big(&[0, 0]));
+        assert_eq!(sequence.extrapolate(-2), big(&[0, 0]));
+    }
+
+    #[test]
+    fn empty() {
+        let sequence = Sequence::fit(&[]);
+        assert_eq!(sequence.degree(), None);
+        assert_eq!(sequence.value_at(0), BigInt::zero());
+        assert_eq!(sequence.extrapolate(2), big(&[0, 0]));
+    }
+
+    #[test]
+    fn constant() {
+        let sequence = Sequence::fit(&[7, 7, 7]);
+        assert_eq!(sequence.degree(), Some(0));
+        assert_eq!(sequence.extrapolate(2), big(&[7, 7]));
+        assert_eq!(sequence.extrapolate(-2), big(&[7, 7]));
+    }
+
+    #[test]
+    fn cubic_both_ways() {
+        let cubic = |n: i64| n * n * n - 2 * n * n + 3;
+        let sequence = Sequence::fit(&(0..5).map(cubic).collect::<Vec<i64>>());
+        assert_eq!(sequence.degree(), Some(3));
+        assert_eq!(
+            sequence.extrapolate(3),
+            big(&[cubic(5), cubic(6), cubic(7)])
+        );
+        assert_eq!(
+            sequence.extrapolate(-3),
+            big(&[cubic(-1), cubic(-2), cubic(-3)])
+        );
+    }
+}